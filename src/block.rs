@@ -1,6 +1,61 @@
 use super::transaction::Transaction;
 use super::utils;
-use super::utils::{BinEncoding, Keccak256};
+use super::utils::{BinEncoding, FixedHash, Keccak256, Sender};
+
+/// Errors which can occur when creating or decoding a [Block](crate::block::Block).
+#[derive(Debug, PartialEq)]
+pub enum BlockError {
+    /// The binary representation couldn't be decoded.
+    InvalidEncoding,
+    /// The recomputed Block id doesn't match the expected id.
+    IdMismatch,
+    /// The Block includes more Transactions than allowed.
+    TooManyTransactions,
+    /// The binary representation of the Block is larger than allowed.
+    BlockTooLarge,
+    /// The Block uses a version which is newer than the supported one.
+    UnsupportedVersion(u16),
+    /// The extra data of the Block is larger than allowed.
+    ExtraDataTooLarge,
+    /// The id of the Transaction at the given position doesn't match its contents.
+    InvalidTransaction(usize),
+}
+
+/// Version of the [Block](crate::block::Block) format created by this code.
+pub const BLOCK_VERSION: u16 = 2;
+
+/// First version of the [Block](crate::block::Block) format which includes extra data.
+const EXTRA_DATA_VERSION: u16 = 2;
+
+/// Maximum size of the extra data included in a [Block](crate::block::Block) in bytes.
+pub const MAX_EXTRA_DATA_BYTES: usize = 64;
+
+/// Limits which bound the resources used by a [Block](crate::block::Block).
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockLimits {
+    /// Maximum number of Transactions included in a Block.
+    pub max_txs: usize,
+    /// Maximum size of a Blocks binary representation in bytes.
+    pub max_bytes: usize,
+}
+
+/// Metadata of a [Block](crate::block::Block) without its Transactions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockHeader {
+    /// Id which uniquely identifies the Block.
+    pub id: Keccak256,
+    /// Id which references the preceding Block.
+    pub prev_block_id: Option<Keccak256>,
+    /// Arbitrary data included by the proposer of the Block.
+    pub extra_data: Vec<u8>,
+}
+
+/// Block data encoded by [compact_ids](crate::block::Block::compact_ids).
+type CompactIdsEncoding = (u16, Vec<([u8; 32], Sender, u64)>, Option<[u8; 32]>);
+
+/// Block data encoded by [compact_ids](crate::block::Block::compact_ids) for
+/// versions which include extra data.
+type CompactIdsExtraEncoding = (u16, Vec<([u8; 32], Sender, u64)>, Option<[u8; 32]>, Vec<u8>);
 
 /// A Block that contains multiple [Transactions](crate::transaction::Transaction).
 #[derive(Debug, Clone, PartialEq)]
@@ -11,52 +66,276 @@ pub struct Block {
     pub transactions: Vec<Transaction>,
     /// Id which references the preceding Block.
     prev_block_id: Option<Keccak256>,
+    /// Arbitrary data included by the proposer of the Block.
+    extra_data: Vec<u8>,
+    /// Version of the Block format.
+    version: u16,
 }
 
 impl Block {
     /// Creates a new Block.
     pub fn new(transactions: Vec<Transaction>, prev_block_id: Option<Keccak256>) -> Self {
-        let id = Block::generate_id(&transactions, prev_block_id.as_ref());
+        Block::with_extra_data(transactions, prev_block_id, Vec::new())
+    }
+
+    /// Creates the genesis Block, i.e. the empty Block without a previous Block.
+    pub fn genesis() -> Self {
+        Block::new(Vec::new(), None)
+    }
+
+    /// Creates a new Block which includes the given extra data without checking
+    /// its size.
+    pub(crate) fn with_extra_data(
+        transactions: Vec<Transaction>,
+        prev_block_id: Option<Keccak256>,
+        extra_data: Vec<u8>,
+    ) -> Self {
+        Block::with_version(BLOCK_VERSION, transactions, prev_block_id, extra_data)
+    }
+
+    /// Creates a new Block using the given format version.
+    fn with_version(
+        version: u16,
+        transactions: Vec<Transaction>,
+        prev_block_id: Option<Keccak256>,
+        extra_data: Vec<u8>,
+    ) -> Self {
+        let id = Block::generate_id(version, &transactions, prev_block_id.as_ref(), &extra_data);
         Block {
             id,
             transactions,
             prev_block_id,
+            extra_data,
+            version,
         }
     }
 
+    /// Creates a Block from its parts without recomputing its id.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn from_parts(
+        id: Keccak256,
+        transactions: Vec<Transaction>,
+        prev_block_id: Option<Keccak256>,
+    ) -> Self {
+        Block {
+            id,
+            transactions,
+            prev_block_id,
+            extra_data: Vec::new(),
+            version: BLOCK_VERSION,
+        }
+    }
+
+    /// Creates a new Block which includes the given extra data if it stays within
+    /// the given limits and [MAX_EXTRA_DATA_BYTES].
+    pub fn try_new(
+        transactions: Vec<Transaction>,
+        prev_block_id: Option<Keccak256>,
+        extra_data: Vec<u8>,
+        limits: &BlockLimits,
+    ) -> Result<Self, BlockError> {
+        if transactions.len() > limits.max_txs {
+            return Err(BlockError::TooManyTransactions);
+        }
+        if extra_data.len() > MAX_EXTRA_DATA_BYTES {
+            return Err(BlockError::ExtraDataTooLarge);
+        }
+        let size = Block::serialize(
+            BLOCK_VERSION,
+            &transactions,
+            prev_block_id.as_ref(),
+            &extra_data,
+        )
+        .len();
+        if size > limits.max_bytes {
+            return Err(BlockError::BlockTooLarge);
+        }
+        Ok(Block::with_extra_data(
+            transactions,
+            prev_block_id,
+            extra_data,
+        ))
+    }
+
+    /// Returns the header of the Block.
+    pub fn header(&self) -> BlockHeader {
+        BlockHeader {
+            id: self.id.clone(),
+            prev_block_id: self.prev_block_id.clone(),
+            extra_data: self.extra_data.clone(),
+        }
+    }
+
+    /// Returns the version of the Block format.
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Returns the extra data included by the proposer of the Block.
+    pub fn extra_data(&self) -> &[u8] {
+        &self.extra_data
+    }
+
     /// Returns a reference to the previous Block id.
     pub fn get_previous_block_id(&self) -> Option<&Keccak256> {
         self.prev_block_id.as_ref()
     }
 
-    /// Sets the previous Block id and updates the Blocks id.
-    pub fn set_previous_block_id(&mut self, prev_block_id: Option<Keccak256>) {
+    /// Sets the previous Block id and updates the Blocks id. Only the
+    /// [Chain](crate::chain::Chain) may re-link Blocks.
+    pub(crate) fn set_previous_block_id(&mut self, prev_block_id: Option<Keccak256>) {
         self.prev_block_id = prev_block_id;
-        self.id = Block::generate_id(&self.transactions, self.prev_block_id.as_ref());
+        self.id = Block::generate_id(
+            self.version,
+            &self.transactions,
+            self.prev_block_id.as_ref(),
+            &self.extra_data,
+        );
+    }
+
+    /// Returns whether the Blocks id matches its contents.
+    pub fn is_valid(&self) -> bool {
+        self.id
+            == Block::generate_id(
+                self.version,
+                &self.transactions,
+                self.prev_block_id.as_ref(),
+                &self.extra_data,
+            )
     }
 
-    /// Generates a unique Block id.
+    /// Generates a unique Block id by hashing its binary representation in the
+    /// Block domain.
     pub fn generate_id(
+        version: u16,
         transactions: &Vec<Transaction>,
         prev_block_id: Option<&Keccak256>,
+        extra_data: &[u8],
     ) -> Keccak256 {
-        let serialized = Block::serialize(&transactions, prev_block_id);
-        utils::hash(&serialized)
+        let serialized = Block::serialize(version, &transactions, prev_block_id, extra_data);
+        utils::hash_block(&serialized)
     }
 
-    /// Serializes the Block data into a binary representation.
+    /// Serializes the Block data into a binary representation. The extra data is
+    /// only encoded by versions which support it.
     pub fn serialize(
+        version: u16,
         transactions: &Vec<Transaction>,
         prev_block_id: Option<&Keccak256>,
+        extra_data: &[u8],
     ) -> BinEncoding<Block> {
-        let values = (transactions, prev_block_id);
-        bincode::serialize(&values).unwrap()
+        if version < EXTRA_DATA_VERSION {
+            let values = (version, transactions, prev_block_id);
+            bincode::serialize(&values).unwrap()
+        } else {
+            let values = (version, transactions, prev_block_id, extra_data);
+            bincode::serialize(&values).unwrap()
+        }
     }
 
     /// Deserializes a Blocks binary representation.
-    pub fn deserialize(data: BinEncoding<Block>) -> Block {
-        let (transactions, prev_block_id) = bincode::deserialize(&data[..]).unwrap();
-        Block::new(transactions, prev_block_id)
+    ///
+    /// The version is decoded first so that Blocks using a newer format are rejected
+    /// before their remaining bytes are interpreted.
+    pub fn deserialize(data: BinEncoding<Block>) -> Result<Block, BlockError> {
+        let version: u16 =
+            bincode::deserialize(&data[..]).map_err(|_| BlockError::InvalidEncoding)?;
+        if version > BLOCK_VERSION {
+            return Err(BlockError::UnsupportedVersion(version));
+        }
+        let (version, transactions, prev_block_id, extra_data): (
+            u16,
+            Vec<Transaction>,
+            Option<Keccak256>,
+            Vec<u8>,
+        ) = if version < EXTRA_DATA_VERSION {
+            let (version, transactions, prev_block_id) =
+                bincode::deserialize(&data[..]).map_err(|_| BlockError::InvalidEncoding)?;
+            (version, transactions, prev_block_id, Vec::new())
+        } else {
+            bincode::deserialize(&data[..]).map_err(|_| BlockError::InvalidEncoding)?
+        };
+        if extra_data.len() > MAX_EXTRA_DATA_BYTES {
+            return Err(BlockError::ExtraDataTooLarge);
+        }
+        if let Some(index) = transactions.iter().position(|tx| !tx.validate()) {
+            return Err(BlockError::InvalidTransaction(index));
+        }
+        Ok(Block::with_version(
+            version,
+            transactions,
+            prev_block_id,
+            extra_data,
+        ))
+    }
+
+    /// Serializes the Block into a binary representation which encodes ids as
+    /// fixed-size arrays. This omits the 8 byte length prefix of every id while
+    /// [serialize](Block::serialize) stays backward compatible.
+    pub fn compact_ids(&self) -> BinEncoding<Block> {
+        let transactions: Vec<(FixedHash, &Sender, u64)> = self
+            .transactions
+            .iter()
+            .map(|tx| (FixedHash(&tx.id), tx.sender(), tx.nonce()))
+            .collect();
+        let prev_block_id = self.prev_block_id.as_ref().map(FixedHash);
+        if self.version < EXTRA_DATA_VERSION {
+            let values = (self.version, transactions, prev_block_id);
+            bincode::serialize(&values).unwrap()
+        } else {
+            let values = (self.version, transactions, prev_block_id, &self.extra_data);
+            bincode::serialize(&values).unwrap()
+        }
+    }
+
+    /// Deserializes a binary representation created by [compact_ids](Block::compact_ids).
+    pub fn from_compact_ids(data: BinEncoding<Block>) -> Result<Block, BlockError> {
+        let version: u16 =
+            bincode::deserialize(&data[..]).map_err(|_| BlockError::InvalidEncoding)?;
+        if version > BLOCK_VERSION {
+            return Err(BlockError::UnsupportedVersion(version));
+        }
+        let (version, transactions, prev_block_id, extra_data): CompactIdsExtraEncoding =
+            if version < EXTRA_DATA_VERSION {
+                let (version, transactions, prev_block_id): CompactIdsEncoding =
+                    bincode::deserialize(&data[..]).map_err(|_| BlockError::InvalidEncoding)?;
+                (version, transactions, prev_block_id, Vec::new())
+            } else {
+                bincode::deserialize(&data[..]).map_err(|_| BlockError::InvalidEncoding)?
+            };
+        if extra_data.len() > MAX_EXTRA_DATA_BYTES {
+            return Err(BlockError::ExtraDataTooLarge);
+        }
+
+        let transactions = transactions
+            .into_iter()
+            .enumerate()
+            .map(|(index, (id, sender, nonce))| {
+                let tx = Transaction::new(sender, nonce);
+                if tx.id != Keccak256::from(id) {
+                    return Err(BlockError::InvalidTransaction(index));
+                }
+                Ok(tx)
+            })
+            .collect::<Result<Vec<Transaction>, BlockError>>()?;
+        Ok(Block::with_version(
+            version,
+            transactions,
+            prev_block_id.map(Keccak256::from),
+            extra_data,
+        ))
+    }
+
+    /// Deserializes a Blocks binary representation and ensures that it matches the expected id.
+    pub fn deserialize_verified(
+        data: BinEncoding<Block>,
+        expected_id: &Keccak256,
+    ) -> Result<Block, BlockError> {
+        let block = Block::deserialize(data)?;
+        if !utils::ct_eq(block.id.as_bytes(), expected_id.as_bytes()) {
+            return Err(BlockError::IdMismatch);
+        }
+        Ok(block)
     }
 }
 
@@ -72,37 +351,279 @@ mod tests {
 
         let block = Block::new(vec![tx_1.clone(), tx_2.clone(), tx_3.clone()], None);
         let expected = Block {
-            id: vec![
-                246, 134, 115, 10, 204, 145, 13, 37, 13, 114, 184, 74, 164, 48, 50, 144, 22, 104,
-                204, 116, 53, 94, 84, 254, 216, 22, 97, 58, 245, 188, 45, 21,
-            ],
+            id: [
+                58, 54, 227, 228, 131, 246, 193, 144, 140, 73, 73, 203, 40, 197, 23, 128, 215, 88,
+                12, 109, 95, 0, 8, 27, 41, 217, 57, 86, 42, 134, 170, 80,
+            ]
+            .into(),
             transactions: vec![tx_1.clone(), tx_2.clone(), tx_3.clone()],
             prev_block_id: None,
+            extra_data: vec![],
+            version: BLOCK_VERSION,
         };
 
         assert_eq!(block, expected);
     }
 
+    #[test]
+    fn empty_block() {
+        let block = Block::new(vec![], None);
+        assert_eq!(Block::genesis(), block);
+        assert_eq!(
+            block.id,
+            Keccak256::from([
+                201, 20, 252, 250, 147, 131, 103, 85, 66, 49, 107, 84, 93, 225, 212, 152, 220, 137,
+                7, 222, 137, 18, 109, 206, 7, 55, 165, 185, 65, 20, 242, 130
+            ])
+        );
+
+        let serialized = Block::serialize(BLOCK_VERSION, &vec![], None, &[]);
+        assert_eq!(
+            serialized,
+            vec![2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+
+        let deserialized = Block::deserialize(serialized);
+        assert_eq!(deserialized, Ok(block));
+    }
+
+    #[test]
+    fn try_new() {
+        let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        let tx_2 = Transaction::new(vec![0, 1, 2, 3, 4], 2);
+        let tx_3 = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+        let limits = BlockLimits {
+            max_txs: 2,
+            max_bytes: 100,
+        };
+
+        // Under both limits.
+        let block = Block::try_new(vec![tx_1.clone()], None, vec![], &limits);
+        assert_eq!(block, Ok(Block::new(vec![tx_1.clone()], None)));
+
+        // Too many Transactions.
+        let block = Block::try_new(
+            vec![tx_1.clone(), tx_2.clone(), tx_3],
+            None,
+            vec![],
+            &limits,
+        );
+        assert_eq!(block, Err(BlockError::TooManyTransactions));
+
+        // Too many bytes (2 Transactions are encoded in 141 bytes).
+        let block = Block::try_new(vec![tx_1.clone(), tx_2], None, vec![], &limits);
+        assert_eq!(block, Err(BlockError::BlockTooLarge));
+
+        // Too much extra data.
+        let extra_data = vec![0; MAX_EXTRA_DATA_BYTES + 1];
+        let block = Block::try_new(vec![tx_1], None, extra_data, &limits);
+        assert_eq!(block, Err(BlockError::ExtraDataTooLarge));
+    }
+
+    #[test]
+    fn extra_data() {
+        let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        let limits = BlockLimits {
+            max_txs: 1,
+            max_bytes: 1000,
+        };
+
+        let block = Block::try_new(vec![tx.clone()], None, vec![1, 2, 3], &limits).unwrap();
+        assert_eq!(block.extra_data(), &[1, 2, 3]);
+        assert_eq!(block.header().extra_data, vec![1, 2, 3]);
+        assert!(block.is_valid());
+
+        // Blocks which only differ in their extra data have different ids.
+        let other = Block::try_new(vec![tx.clone()], None, vec![4, 5, 6], &limits).unwrap();
+        assert_ne!(block.id, other.id);
+        assert_ne!(block.id, Block::new(vec![tx], None).id);
+
+        let serialized =
+            Block::serialize(BLOCK_VERSION, &block.transactions, None, block.extra_data());
+        assert_eq!(Block::deserialize(serialized), Ok(block.clone()));
+        assert_eq!(Block::from_compact_ids(block.compact_ids()), Ok(block));
+
+        // Decoded extra data has to stay within the limit.
+        let extra_data = vec![0; MAX_EXTRA_DATA_BYTES + 1];
+        let serialized = Block::serialize(BLOCK_VERSION, &vec![], None, &extra_data);
+        assert_eq!(
+            Block::deserialize(serialized),
+            Err(BlockError::ExtraDataTooLarge)
+        );
+    }
+
     #[test]
     fn serde() {
         let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
         let transactions = vec![tx_1];
-        let prev_block_id = Some(vec![5, 6, 7, 8, 9]);
+        let prev_block_id = Some(Keccak256::from([5; 32]));
         let block = Block::new(transactions.clone(), prev_block_id.clone());
 
-        let serialized = Block::serialize(&transactions, prev_block_id.clone().as_ref());
+        let serialized = Block::serialize(
+            BLOCK_VERSION,
+            &transactions,
+            prev_block_id.clone().as_ref(),
+            &[],
+        );
         assert_eq!(
             serialized,
             vec![
-                1, 0, 0, 0, 0, 0, 0, 0, 32, 0, 0, 0, 0, 0, 0, 0, 196, 70, 213, 169, 141, 198, 53,
-                47, 112, 185, 125, 254, 146, 41, 135, 204, 30, 126, 28, 159, 0, 167, 6, 219, 32,
-                215, 216, 240, 151, 197, 172, 26, 5, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4, 1, 0, 0,
-                0, 0, 0, 0, 0, 1, 5, 0, 0, 0, 0, 0, 0, 0, 5, 6, 7, 8, 9
+                2, 0, 1, 0, 0, 0, 0, 0, 0, 0, 32, 0, 0, 0, 0, 0, 0, 0, 180, 135, 82, 97, 46, 76,
+                226, 163, 226, 110, 10, 40, 14, 175, 96, 176, 216, 85, 199, 40, 9, 126, 151, 81,
+                14, 164, 150, 109, 104, 62, 54, 128, 5, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4, 1, 0,
+                0, 0, 0, 0, 0, 0, 1, 32, 0, 0, 0, 0, 0, 0, 0, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5,
+                5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 0, 0, 0, 0, 0, 0, 0, 0
             ]
         );
 
         let deserialized = Block::deserialize(serialized);
-        assert_eq!(deserialized, block);
+        assert_eq!(deserialized, Ok(block));
+    }
+
+    #[test]
+    fn deserialize_older_version() {
+        let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        let transactions = vec![tx];
+
+        for version in 0..BLOCK_VERSION {
+            let serialized = Block::serialize(version, &transactions, None, &[]);
+            let deserialized = Block::deserialize(serialized).unwrap();
+            assert_eq!(deserialized.version(), version);
+            assert_eq!(
+                deserialized.id,
+                Block::generate_id(version, &transactions, None, &[])
+            );
+            assert!(deserialized.is_valid());
+        }
+
+        // Versions without extra data don't encode it.
+        let serialized = Block::serialize(1, &transactions, None, &[]);
+        assert_eq!(
+            serialized.len() + 8,
+            Block::serialize(BLOCK_VERSION, &transactions, None, &[]).len()
+        );
+    }
+
+    #[test]
+    fn deserialize_future_version() {
+        let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        let transactions = vec![tx];
+
+        let serialized = Block::serialize(BLOCK_VERSION + 1, &transactions, None, &[]);
+        let deserialized = Block::deserialize(serialized);
+        assert_eq!(
+            deserialized,
+            Err(BlockError::UnsupportedVersion(BLOCK_VERSION + 1))
+        );
+
+        // Only the version prefix of a future Block is interpreted.
+        let deserialized = Block::deserialize(vec![255, 255, 1, 2, 3]);
+        assert_eq!(deserialized, Err(BlockError::UnsupportedVersion(u16::MAX)));
+    }
+
+    #[test]
+    fn deserialize_invalid_transaction() {
+        let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        let tampered = Transaction::from_parts([0; 32].into(), vec![5, 6, 7, 8, 9], 1);
+        let transactions = vec![tx, tampered];
+
+        let serialized = Block::serialize(BLOCK_VERSION, &transactions, None, &[]);
+        assert_eq!(
+            Block::deserialize(serialized),
+            Err(BlockError::InvalidTransaction(1))
+        );
+
+        let compact = Block::from_parts([0; 32].into(), transactions, None).compact_ids();
+        assert_eq!(
+            Block::from_compact_ids(compact),
+            Err(BlockError::InvalidTransaction(1))
+        );
+    }
+
+    #[test]
+    fn compact_ids() {
+        let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        let block = Block::new(vec![tx], Some([5; 32].into()));
+
+        // The Transaction id and the previous Block id lose their length prefix.
+        let default = Block::serialize(
+            BLOCK_VERSION,
+            &block.transactions,
+            block.get_previous_block_id(),
+            block.extra_data(),
+        );
+        let compact = block.compact_ids();
+        assert_eq!(default.len() - compact.len(), 2 * 8);
+        assert_eq!(Block::from_compact_ids(compact), Ok(block));
+
+        let empty_block = Block::new(vec![], None);
+        let compact = empty_block.compact_ids();
+        assert_eq!(Block::from_compact_ids(compact), Ok(empty_block));
+
+        assert_eq!(
+            Block::from_compact_ids(vec![1, 0, 1]),
+            Err(BlockError::InvalidEncoding)
+        );
+    }
+
+    #[test]
+    fn deserialize_verified() {
+        let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        let transactions = vec![tx];
+        let block = Block::new(transactions.clone(), Some([5; 32].into()));
+
+        let serialized = Block::serialize(
+            BLOCK_VERSION,
+            &transactions,
+            block.get_previous_block_id(),
+            &[],
+        );
+        let deserialized = Block::deserialize_verified(serialized, &block.id);
+        assert_eq!(deserialized, Ok(block.clone()));
+
+        // Bytes with a corrupted previous Block id.
+        let serialized = Block::serialize(BLOCK_VERSION, &transactions, Some(&[0; 32].into()), &[]);
+        let deserialized = Block::deserialize_verified(serialized, &block.id);
+        assert_eq!(deserialized, Err(BlockError::IdMismatch));
+
+        // Bytes which can't be decoded.
+        let deserialized = Block::deserialize_verified(vec![1, 2, 3], &block.id);
+        assert_eq!(deserialized, Err(BlockError::InvalidEncoding));
+    }
+
+    #[test]
+    fn header() {
+        let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        let block = Block::new(vec![tx], Some([5; 32].into()));
+
+        let header = block.header();
+        assert_eq!(header.id, block.id);
+        assert_eq!(header.prev_block_id, Some(Keccak256::from([5; 32])));
+    }
+
+    #[test]
+    fn is_valid() {
+        let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+
+        let mut block = Block::new(vec![tx], None);
+        assert!(block.is_valid());
+
+        // Tamper with the Block id
+        block.id = [0; 32].into();
+        assert!(!block.is_valid());
+    }
+
+    #[test]
+    fn from_parts() {
+        let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+
+        let block = Block::from_parts([0; 32].into(), vec![tx.clone()], None);
+        assert_eq!(block.version(), BLOCK_VERSION);
+        assert!(!block.is_valid());
+
+        let valid = Block::new(vec![tx.clone()], None);
+        let block = Block::from_parts(valid.id.clone(), vec![tx], None);
+        assert_eq!(block, valid);
     }
 
     #[test]
@@ -111,24 +632,30 @@ mod tests {
 
         let mut block = Block::new(vec![tx.clone()], None);
         let expected_initial = Block {
-            id: vec![
-                61, 76, 173, 32, 98, 204, 110, 230, 105, 241, 153, 253, 74, 212, 214, 61, 101, 52,
-                42, 176, 46, 29, 206, 216, 251, 40, 250, 159, 168, 103, 81, 99,
-            ],
+            id: [
+                153, 173, 85, 81, 119, 241, 7, 194, 152, 12, 210, 172, 232, 36, 246, 207, 177, 235,
+                206, 126, 113, 204, 162, 245, 113, 241, 161, 186, 20, 188, 193, 144,
+            ]
+            .into(),
             transactions: vec![tx.clone()],
             prev_block_id: None,
+            extra_data: vec![],
+            version: BLOCK_VERSION,
         };
         assert_eq!(block, expected_initial);
 
         // Update the previous Block id
-        block.set_previous_block_id(Some(vec![1, 2, 3, 4]));
+        block.set_previous_block_id(Some([1; 32].into()));
         let expected_updated = Block {
-            id: vec![
-                137, 184, 196, 140, 0, 212, 191, 29, 101, 3, 16, 175, 81, 94, 71, 5, 59, 215, 214,
-                187, 147, 58, 226, 21, 220, 250, 77, 67, 131, 51, 91, 60,
-            ],
+            id: [
+                76, 249, 14, 37, 74, 165, 87, 115, 139, 101, 119, 130, 220, 156, 14, 68, 151, 222,
+                18, 17, 68, 103, 122, 128, 221, 63, 185, 89, 44, 114, 179, 2,
+            ]
+            .into(),
             transactions: vec![tx.clone()],
-            prev_block_id: Some(vec![1, 2, 3, 4]),
+            prev_block_id: Some([1; 32].into()),
+            extra_data: vec![],
+            version: BLOCK_VERSION,
         };
         assert_eq!(block, expected_updated);
     }
@@ -1,56 +1,380 @@
-use super::block::Block;
+use std::{collections::VecDeque, fmt};
+
+use super::block::{Block, BlockHeader};
+use super::transaction::Transaction;
+use super::utils;
+use super::utils::{Domain, Keccak256};
+
+/// Errors which can occur when validating a [Chain](crate::chain::Chain).
+#[derive(Debug, PartialEq)]
+pub enum ChainError {
+    /// The Block at the given height or one of its Transactions doesn't match its id.
+    InvalidBlock(u64),
+    /// The Block at the given height doesn't reference its preceding Block.
+    InvalidPreviousBlock(u64),
+    /// The Chain doesn't include a Block at the given height.
+    UnknownHeight(u64),
+}
+
+/// A summary of the state of a [Chain](crate::chain::Chain).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainSummary {
+    /// Height of the last Block.
+    pub height: Option<u64>,
+    /// Number of Transactions included in all Blocks.
+    pub total_transactions: usize,
+    /// Id of the last Block.
+    pub tip_id: Option<Keccak256>,
+    /// Fingerprint of all Block ids.
+    pub fingerprint: Keccak256,
+}
+
+/// The Blocks two [Chains](crate::chain::Chain) don't share, starting at the
+/// first height at which they diverge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainDiff {
+    /// Heights of the local Blocks which the peer doesn't have.
+    pub missing_for_them: Vec<u64>,
+    /// Ids of the peers Blocks which aren't part of the local Chain.
+    pub they_have_i_dont: Vec<Keccak256>,
+}
 
 /// An immutable Chain made up of multiple [Blocks](crate::block::Block).
-pub struct Chain(Vec<Block>);
+pub struct Chain {
+    /// Blocks in the order they were appended.
+    blocks: VecDeque<Block>,
+    /// Cached id of the last Block.
+    tip_id: Option<Keccak256>,
+    /// Cached height of the last Block.
+    height: Option<u64>,
+    /// Maximum number of Blocks kept in memory.
+    window: Option<usize>,
+    /// Height of the first Block kept in memory.
+    first_height: u64,
+}
 
 impl Chain {
     /// Creates a new Chain.
     pub fn new(init_capacity: usize) -> Self {
-        let chain: Vec<Block> = Vec::with_capacity(init_capacity);
-        Chain(chain)
+        Chain::with_capacity(init_capacity)
     }
 
-    /// Appends a new Block and returns the current height.
-    pub fn append(&mut self, mut block: Block) -> u64 {
-        let previous_block = self.0.last();
-        let mut previous_block_id = None;
-        if let Some(prev_block) = previous_block {
-            previous_block_id = Some(prev_block.id.clone());
+    /// Creates a new Chain which can hold `capacity` Blocks without reallocating.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let blocks: VecDeque<Block> = VecDeque::with_capacity(capacity);
+        Chain {
+            blocks,
+            tip_id: None,
+            height: None,
+            window: None,
+            first_height: 0,
         }
-        block.set_previous_block_id(previous_block_id);
-        self.0.push(block);
-        // We can safely unwrap here given that we just appended a Block
-        self.height().unwrap()
+    }
+
+    /// Creates a new Chain which only keeps the last `window` Blocks in memory.
+    /// Heights keep counting all appended Blocks. The last Block is always kept,
+    /// so a window of 0 is treated as a window of 1.
+    pub fn with_window(window: usize) -> Self {
+        let window = window.max(1);
+        let mut chain = Chain::with_capacity(window);
+        chain.window = Some(window);
+        chain
+    }
+
+    /// Returns the number of Blocks the Chain can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.blocks.capacity()
+    }
+
+    /// Reserves capacity for at least `additional` more Blocks.
+    pub fn reserve(&mut self, additional: usize) {
+        self.blocks.reserve(additional);
+    }
+
+    /// Appends a new Block and returns the current height. The Blocks previous
+    /// Block id is rewritten to reference the last Block, so this is only meant
+    /// for Blocks assembled within the crate. Use [append_linked](Chain::append_linked)
+    /// for all other Blocks.
+    pub(crate) fn append(&mut self, mut block: Block) -> u64 {
+        block.set_previous_block_id(self.tip_id.clone());
+        let (height, _) = self.push(block);
+        height
+    }
+
+    /// Appends a Block which already references the last Block and returns the
+    /// current height. The Blocks previous Block id is never rewritten, so only
+    /// the first Block may omit it.
+    pub fn append_linked(&mut self, block: Block) -> Result<u64, ChainError> {
+        self.append_with_eviction(block).map(|(height, _)| height)
+    }
+
+    /// Appends a Block which already references the last Block and returns the
+    /// current height alongside the oldest Block if it dropped out of the window.
+    pub fn append_with_eviction(
+        &mut self,
+        block: Block,
+    ) -> Result<(u64, Option<Block>), ChainError> {
+        if block.get_previous_block_id() != self.tip_id() {
+            return Err(ChainError::InvalidPreviousBlock(self.next_height()));
+        }
+        Ok(self.push(block))
+    }
+
+    /// Pushes a Block onto the Chain and evicts the oldest Block if it dropped out
+    /// of the window. The Block has to reference the last Block.
+    fn push(&mut self, block: Block) -> (u64, Option<Block>) {
+        self.tip_id = Some(block.id.clone());
+        self.blocks.push_back(block);
+        let height = self.next_height();
+        self.height = Some(height);
+
+        let evicted = match self.window {
+            Some(window) if self.blocks.len() > window => {
+                self.first_height += 1;
+                self.blocks.pop_front()
+            }
+            _ => None,
+        };
+        (height, evicted)
     }
 
     /// Returns the current height.
     pub fn height(&self) -> Option<u64> {
-        if self.0.is_empty() {
-            return None;
+        self.height
+    }
+
+    /// Returns the height of the next appended Block.
+    fn next_height(&self) -> u64 {
+        self.height.map_or(0, |height| height + 1)
+    }
+
+    /// Returns the position of the Block at the given height in memory.
+    fn index_of(&self, height: u64) -> Option<usize> {
+        height
+            .checked_sub(self.first_height)
+            .map(|index| index as usize)
+    }
+
+    /// Returns a reference to the id of the last Block.
+    pub fn tip_id(&self) -> Option<&Keccak256> {
+        self.tip_id.as_ref()
+    }
+
+    /// Returns a reference to the Block at the given height. Blocks which dropped
+    /// out of the window aren't available.
+    pub fn get(&self, height: usize) -> Option<&Block> {
+        let index = self.index_of(height as u64)?;
+        self.blocks.get(index)
+    }
+
+    /// Re-links the Block at the given height to the given previous Block, which
+    /// changes its id. The cached tip id is updated if the Block is the last one.
+    ///
+    /// Only meant for reorganizing the Chain within the crate. The following
+    /// Blocks have to be re-linked as well to keep the Chain consistent.
+    pub(crate) fn relink(
+        &mut self,
+        height: u64,
+        prev_block_id: Option<Keccak256>,
+    ) -> Result<(), ChainError> {
+        let index = self
+            .index_of(height)
+            .filter(|index| *index < self.blocks.len())
+            .ok_or(ChainError::UnknownHeight(height))?;
+        let is_tip = index == self.blocks.len() - 1;
+        let block = &mut self.blocks[index];
+        block.set_previous_block_id(prev_block_id);
+        if is_tip {
+            self.tip_id = Some(block.id.clone());
         }
-        Some((self.0.len() - 1) as u64)
+        Ok(())
+    }
+
+    /// Returns an iterator over all Blocks in the order they were appended.
+    pub fn iter(&self) -> impl Iterator<Item = &Block> {
+        self.blocks.iter()
+    }
+
+    /// Returns an iterator over all Blocks from the tip back to the first Block.
+    pub fn iter_rev(&self) -> impl Iterator<Item = &Block> {
+        self.blocks.iter().rev()
     }
 
-    /// Returns a reference to the Block at the given index.
-    pub fn get(&self, index: usize) -> Option<&Block> {
-        self.0.get(index)
+    /// Returns the last `n` Blocks, newest first.
+    pub fn recent(&self, n: usize) -> Vec<&Block> {
+        self.iter_rev().take(n).collect()
+    }
+
+    /// Returns an iterator over the headers of all Blocks in the order they were appended.
+    pub fn iter_headers(&self) -> impl Iterator<Item = BlockHeader> + '_ {
+        self.blocks.iter().map(|block| block.header())
+    }
+
+    /// Returns the height of the Block which includes the Transaction with the
+    /// given id alongside a reference to the Transaction.
+    pub fn find_transaction(&self, tx_id: &Keccak256) -> Option<(u64, &Transaction)> {
+        self.blocks.iter().enumerate().find_map(|(index, block)| {
+            block
+                .transactions
+                .iter()
+                .find(|tx| &tx.id == tx_id)
+                .map(|tx| (self.first_height + index as u64, tx))
+        })
     }
 
     /// Returns a reference to the last Block.
     pub fn last(&self) -> Option<&Block> {
-        self.0.last()
+        self.blocks.back()
+    }
+
+    /// Replaces the Block at the given height and re-links all subsequent Blocks,
+    /// which updates their ids. The replacement has to reference the same
+    /// preceding Block as the Block it replaces.
+    pub fn replace_block(&mut self, height: u64, block: Block) -> Result<(), ChainError> {
+        let index = self
+            .index_of(height)
+            .ok_or(ChainError::UnknownHeight(height))?;
+        let replaced = self
+            .blocks
+            .get(index)
+            .ok_or(ChainError::UnknownHeight(height))?;
+        if !block.is_valid() || !block.transactions.iter().all(|tx| tx.validate()) {
+            return Err(ChainError::InvalidBlock(height));
+        }
+        if block.get_previous_block_id() != replaced.get_previous_block_id() {
+            return Err(ChainError::InvalidPreviousBlock(height));
+        }
+
+        self.blocks[index] = block;
+        for index in index + 1..self.blocks.len() {
+            let prev_block_id = self.blocks[index - 1].id.clone();
+            self.relink(self.first_height + index as u64, Some(prev_block_id))?;
+        }
+        self.tip_id = self.blocks.back().map(|block| block.id.clone());
+        Ok(())
+    }
+
+    /// Ensures that all Blocks and Transactions match their ids and that every
+    /// Block references its preceding Block. Only the Blocks kept in memory are
+    /// checked.
+    pub fn validate(&self) -> Result<(), ChainError> {
+        // The first Block kept in memory references a Block which dropped out of
+        // the window.
+        let mut prev_block_id = match self.first_height {
+            0 => None,
+            _ => self
+                .blocks
+                .front()
+                .and_then(|block| block.get_previous_block_id()),
+        };
+        for (index, block) in self.blocks.iter().enumerate() {
+            let height = self.first_height + index as u64;
+            if !block.is_valid() || !block.transactions.iter().all(|tx| tx.validate()) {
+                return Err(ChainError::InvalidBlock(height));
+            }
+            if block.get_previous_block_id() != prev_block_id {
+                return Err(ChainError::InvalidPreviousBlock(height));
+            }
+            prev_block_id = Some(&block.id);
+        }
+        Ok(())
+    }
+
+    /// Returns a fingerprint which folds the ids of all Blocks kept in memory in order.
+    pub fn fingerprint(&self) -> Keccak256 {
+        let mut fingerprint = utils::hash(Domain::Fingerprint, b"");
+        for block in self.blocks.iter() {
+            fingerprint = utils::hash(
+                Domain::Fingerprint,
+                [fingerprint.as_bytes(), block.id.as_bytes()].concat(),
+            );
+        }
+        fingerprint
+    }
+
+    /// Returns a summary of the Chain.
+    pub fn summary(&self) -> ChainSummary {
+        ChainSummary {
+            height: self.height,
+            total_transactions: self
+                .blocks
+                .iter()
+                .map(|block| block.transactions.len())
+                .sum(),
+            tip_id: self.tip_id.clone(),
+            fingerprint: self.fingerprint(),
+        }
+    }
+
+    /// Compares the Chain with the Block ids of a peer, ordered by height, and
+    /// returns the Blocks which differ from the first height at which the Chains
+    /// diverge.
+    pub fn diff(&self, their_block_ids: &[Keccak256]) -> ChainDiff {
+        // Only the Blocks kept in memory can be compared.
+        let their_block_ids = their_block_ids
+            .get(self.first_height as usize..)
+            .unwrap_or(&[]);
+        let divergence = self
+            .blocks
+            .iter()
+            .zip(their_block_ids.iter())
+            .position(|(block, id)| &block.id != id)
+            .unwrap_or_else(|| self.blocks.len().min(their_block_ids.len()));
+        ChainDiff {
+            missing_for_them: (divergence..self.blocks.len())
+                .map(|index| self.first_height + index as u64)
+                .collect(),
+            they_have_i_dont: their_block_ids[divergence..].to_vec(),
+        }
+    }
+
+    /// Returns references to all Blocks appended after the Block with the given id.
+    pub fn blocks_since(&self, last_known_id: &Keccak256) -> Option<Vec<&Block>> {
+        let index = self
+            .blocks
+            .iter()
+            .position(|block| &block.id == last_known_id)?;
+        Some(self.blocks.iter().skip(index + 1).collect())
+    }
+}
+
+impl fmt::Display for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let summary = self.summary();
+        let height = summary
+            .height
+            .map_or_else(|| "-".to_string(), |height| height.to_string());
+        let tip_id = summary
+            .tip_id
+            .map_or_else(|| "-".to_string(), utils::to_hex);
+        writeln!(f, "Height: {}", height)?;
+        writeln!(f, "Transactions: {}", summary.total_transactions)?;
+        writeln!(f, "Tip: {}", tip_id)?;
+        write!(f, "Fingerprint: {}", utils::to_hex(&summary.fingerprint))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::transaction::Transaction;
 
     #[test]
     fn new_chain() {
         let chain = Chain::new(100);
-        assert_eq!(chain.0.len(), 0);
+        assert_eq!(chain.blocks.len(), 0);
+        assert_eq!(chain.tip_id(), None);
+        assert_eq!(chain.height(), None);
+    }
+
+    #[test]
+    fn capacity() {
+        let mut chain = Chain::with_capacity(10);
+        assert!(chain.capacity() >= 10);
+
+        let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        chain.append(Block::new(vec![tx], None));
+        chain.reserve(100);
+        assert!(chain.capacity() >= 101);
     }
 
     #[test]
@@ -65,6 +389,40 @@ mod tests {
         assert_eq!(chain.height(), Some(0));
     }
 
+    #[test]
+    fn append_linked() {
+        let mut chain = Chain::new(2);
+        let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        let tx_2 = Transaction::new(vec![0, 1, 2, 3, 4], 2);
+
+        let genesis = Block::new(vec![tx_1], None);
+        assert_eq!(chain.append_linked(genesis.clone()), Ok(0));
+
+        // Blocks which don't reference the tip are rejected.
+        let block = Block::new(vec![tx_2.clone()], Some([1; 32].into()));
+        assert_eq!(
+            chain.append_linked(block),
+            Err(ChainError::InvalidPreviousBlock(1))
+        );
+        let block = Block::new(vec![tx_2.clone()], None);
+        assert_eq!(
+            chain.append_linked(block),
+            Err(ChainError::InvalidPreviousBlock(1))
+        );
+
+        let block = Block::new(vec![tx_2], Some(genesis.id.clone()));
+        assert_eq!(chain.append_linked(block.clone()), Ok(1));
+        assert_eq!(chain.last(), Some(&block));
+
+        // Blocks with a mismatching previous Block id don't evict anything either.
+        let block = Block::new(vec![], None);
+        assert_eq!(
+            chain.append_with_eviction(block),
+            Err(ChainError::InvalidPreviousBlock(2))
+        );
+        assert_eq!(chain.height(), Some(1));
+    }
+
     #[test]
     fn get() {
         let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
@@ -87,6 +445,338 @@ mod tests {
         assert_eq!(chain.last(), Some(&block));
     }
 
+    #[test]
+    fn tip_id() {
+        let mut chain = Chain::new(100);
+
+        for nonce in 0..50 {
+            let tx = Transaction::new(vec![0, 1, 2, 3, 4], nonce);
+            let block = Block::new(vec![tx], None);
+            let height = chain.append(block);
+
+            assert_eq!(chain.tip_id(), Some(&chain.last().unwrap().id));
+            assert_eq!(chain.height(), Some(height));
+        }
+        assert_eq!(chain.height(), Some(49));
+    }
+
+    #[test]
+    fn summary() {
+        let mut chain = Chain::new(3);
+        for nonce in 1..=3 {
+            let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], nonce);
+            let tx_2 = Transaction::new(vec![5, 6, 7, 8, 9], nonce);
+            chain.append(Block::new(vec![tx_1, tx_2], None));
+        }
+
+        let summary = chain.summary();
+        assert_eq!(summary.height, Some(2));
+        assert_eq!(summary.total_transactions, 6);
+        assert_eq!(summary.tip_id, Some(chain.get(2).unwrap().id.clone()));
+        assert_eq!(summary.fingerprint, chain.fingerprint());
+
+        let display = chain.to_string();
+        let tip_line = format!("Tip: {}", utils::to_hex(&chain.get(2).unwrap().id));
+        assert!(display.starts_with("Height: 2\nTransactions: 6\n"));
+        assert!(display.contains(&tip_line));
+    }
+
+    #[test]
+    fn display_empty_chain() {
+        let chain = Chain::new(0);
+
+        assert_eq!(
+            chain.to_string(),
+            "Height: -\nTransactions: 0\nTip: -\nFingerprint: \
+             fe5bda72017c0922b9c696139dbf58f9c14b33a11c4e0af9d9fb9b5fd4437c99"
+        );
+    }
+
+    #[test]
+    fn relink() {
+        let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        let mut chain = Chain::new(1);
+        chain.append(Block::new(vec![tx], None));
+        let old_id = chain.get(0).unwrap().id.clone();
+
+        assert_eq!(chain.relink(0, Some([1; 32].into())), Ok(()));
+        let block = chain.get(0).unwrap();
+        assert_eq!(
+            block.get_previous_block_id(),
+            Some(&Keccak256::from([1; 32]))
+        );
+        assert_ne!(block.id, old_id);
+        assert!(block.is_valid());
+
+        // The re-linked Block is the tip, so the cached tip id follows it.
+        assert_eq!(chain.tip_id(), Some(&block.id));
+        assert_eq!(chain.height(), Some(0));
+        assert_eq!(chain.relink(1, None), Err(ChainError::UnknownHeight(1)));
+    }
+
+    #[test]
+    fn iter() {
+        let mut chain = Chain::new(2);
+        for nonce in 1..=2 {
+            let tx = Transaction::new(vec![0, 1, 2, 3, 4], nonce);
+            chain.append(Block::new(vec![tx], None));
+        }
+
+        let blocks: Vec<&Block> = chain.iter().collect();
+        assert_eq!(blocks, vec![chain.get(0).unwrap(), chain.get(1).unwrap()]);
+    }
+
+    #[test]
+    fn with_window() {
+        let mut chain = Chain::with_window(3);
+        let mut blocks = Vec::new();
+        for nonce in 0..5 {
+            let tx = Transaction::new(vec![0, 1, 2, 3, 4], nonce);
+            let block = Block::new(vec![tx], chain.tip_id().cloned());
+            let (height, evicted) = chain.append_with_eviction(block).unwrap();
+            assert_eq!(height, nonce);
+            // The oldest Block is returned once the window is full.
+            match nonce {
+                0..=2 => assert_eq!(evicted, None),
+                _ => assert_eq!(evicted.as_ref(), blocks.get(nonce as usize - 3)),
+            }
+            blocks.push(chain.last().unwrap().clone());
+        }
+
+        // Only the last 3 Blocks are kept while the height counts all of them.
+        assert_eq!(chain.height(), Some(4));
+        assert_eq!(chain.iter().count(), 3);
+        assert_eq!(chain.get(1), None);
+        assert_eq!(chain.get(2), Some(&blocks[2]));
+        assert_eq!(chain.get(4), Some(&blocks[4]));
+        assert_eq!(chain.tip_id(), Some(&blocks[4].id));
+        let tx_id = &blocks[3].transactions[0].id;
+        assert_eq!(
+            chain.find_transaction(tx_id).map(|(height, _)| height),
+            Some(3)
+        );
+
+        // The retained Blocks are still linked.
+        assert_eq!(chain.validate(), Ok(()));
+        let tx = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+        let block = Block::new(vec![tx], chain.tip_id().cloned());
+        assert_eq!(chain.append_linked(block), Ok(5));
+        assert_eq!(chain.get(2), None);
+
+        // A window of 0 still keeps the last Block.
+        let mut chain = Chain::with_window(0);
+        for nonce in 0..2 {
+            let tx = Transaction::new(vec![0, 1, 2, 3, 4], nonce);
+            chain.append(Block::new(vec![tx], None));
+        }
+        assert_eq!(chain.iter().count(), 1);
+        assert_eq!(chain.height(), Some(1));
+        assert_eq!(chain.last().map(|block| &block.id), chain.tip_id());
+    }
+
+    #[test]
+    fn iter_rev() {
+        let mut chain = Chain::new(3);
+        for nonce in 1..=3 {
+            let tx = Transaction::new(vec![0, 1, 2, 3, 4], nonce);
+            chain.append(Block::new(vec![tx], None));
+        }
+
+        let blocks: Vec<&Block> = chain.iter_rev().collect();
+        let expected: Vec<&Block> = (0..3)
+            .rev()
+            .map(|height| chain.get(height).unwrap())
+            .collect();
+        assert_eq!(blocks, expected);
+        assert_eq!(chain.iter_rev().next(), chain.last());
+    }
+
+    #[test]
+    fn recent() {
+        let mut chain = Chain::new(3);
+        assert!(chain.recent(2).is_empty());
+
+        for nonce in 1..=3 {
+            let tx = Transaction::new(vec![0, 1, 2, 3, 4], nonce);
+            chain.append(Block::new(vec![tx], None));
+        }
+
+        let blocks = chain.recent(2);
+        assert_eq!(blocks, vec![chain.get(2).unwrap(), chain.get(1).unwrap()]);
+        assert_eq!(chain.recent(5).len(), 3);
+    }
+
+    #[test]
+    fn diff() {
+        let mut chain = Chain::new(4);
+        let mut other = Chain::new(3);
+        for nonce in 1..=4 {
+            let tx = Transaction::new(vec![0, 1, 2, 3, 4], nonce);
+            let prev_block_id = chain.tip_id().cloned();
+            chain.append(Block::new(vec![tx.clone()], prev_block_id));
+            if nonce <= 2 {
+                let prev_block_id = other.tip_id().cloned();
+                other.append(Block::new(vec![tx], prev_block_id));
+            }
+        }
+        let tx = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+        let prev_block_id = other.tip_id().cloned();
+        other.append(Block::new(vec![tx], prev_block_id));
+
+        // The Chains share the first 2 Blocks and diverge at height 2.
+        let their_block_ids: Vec<Keccak256> = other.iter().map(|block| block.id.clone()).collect();
+        let diff = chain.diff(&their_block_ids);
+        assert_eq!(diff.missing_for_them, vec![2, 3]);
+        assert_eq!(
+            diff.they_have_i_dont,
+            vec![other.get(2).unwrap().id.clone()]
+        );
+
+        // A peer with a prefix of the Chain only misses the remaining Blocks.
+        let diff = chain.diff(&their_block_ids[..2]);
+        assert_eq!(diff.missing_for_them, vec![2, 3]);
+        assert!(diff.they_have_i_dont.is_empty());
+
+        // Identical Chains don't differ.
+        let own_block_ids: Vec<Keccak256> = chain.iter().map(|block| block.id.clone()).collect();
+        let diff = chain.diff(&own_block_ids);
+        assert!(diff.missing_for_them.is_empty());
+        assert!(diff.they_have_i_dont.is_empty());
+    }
+
+    #[test]
+    fn iter_headers() {
+        let mut chain = Chain::new(3);
+        for nonce in 1..=3 {
+            let tx = Transaction::new(vec![0, 1, 2, 3, 4], nonce);
+            chain.append(Block::new(vec![tx], None));
+        }
+
+        let headers: Vec<BlockHeader> = chain.iter_headers().collect();
+        assert_eq!(headers.len(), 3);
+        for (header, block) in headers.iter().zip(chain.iter()) {
+            assert_eq!(header.id, block.id);
+            assert_eq!(header.prev_block_id.as_ref(), block.get_previous_block_id());
+        }
+    }
+
+    #[test]
+    fn find_transaction() {
+        let mut chain = Chain::new(2);
+        let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        let tx_2 = Transaction::new(vec![0, 1, 2, 3, 4], 2);
+        chain.append(Block::new(vec![tx_1.clone()], None));
+        chain.append(Block::new(vec![tx_2.clone()], None));
+
+        assert_eq!(chain.find_transaction(&tx_1.id), Some((0, &tx_1)));
+        assert_eq!(chain.find_transaction(&tx_2.id), Some((1, &tx_2)));
+        assert_eq!(chain.find_transaction(&[0; 32].into()), None);
+    }
+
+    #[test]
+    fn blocks_since() {
+        let mut chain = Chain::new(3);
+        for nonce in 1..=3 {
+            let tx = Transaction::new(vec![0, 1, 2, 3, 4], nonce);
+            chain.append(Block::new(vec![tx], None));
+        }
+        let first_block = chain.get(0).unwrap();
+        let second_block = chain.get(1).unwrap();
+        let third_block = chain.get(2).unwrap();
+
+        // Known id in the middle of the Chain.
+        assert_eq!(
+            chain.blocks_since(&first_block.id),
+            Some(vec![second_block, third_block])
+        );
+
+        // Id of the tip.
+        assert_eq!(chain.blocks_since(&third_block.id), Some(vec![]));
+
+        // Unknown id.
+        assert_eq!(chain.blocks_since(&[0; 32].into()), None);
+    }
+
+    #[test]
+    fn validate() {
+        let mut chain = Chain::new(3);
+        for nonce in 1..=3 {
+            let tx = Transaction::new(vec![0, 1, 2, 3, 4], nonce);
+            chain.append(Block::new(vec![tx], None));
+        }
+        assert_eq!(chain.validate(), Ok(()));
+
+        // Break the linkage between the first and the second Block.
+        chain.blocks[1].set_previous_block_id(Some([0; 32].into()));
+        assert_eq!(chain.validate(), Err(ChainError::InvalidPreviousBlock(1)));
+
+        // Tamper with the id of the second Block.
+        chain.blocks[1].id = [0; 32].into();
+        assert_eq!(chain.validate(), Err(ChainError::InvalidBlock(1)));
+    }
+
+    #[test]
+    fn replace_block() {
+        let mut chain = Chain::new(3);
+        for nonce in 1..=3 {
+            let tx = Transaction::new(vec![0, 1, 2, 3, 4], nonce);
+            chain.append(Block::new(vec![tx], None));
+        }
+        let old_ids: Vec<Keccak256> = chain.iter().map(|block| block.id.clone()).collect();
+
+        let tx = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+        let replacement = Block::new(vec![tx], Some(old_ids[0].clone()));
+        chain.replace_block(1, replacement.clone()).unwrap();
+
+        assert_eq!(chain.get(0).unwrap().id, old_ids[0]);
+        assert_eq!(chain.get(1), Some(&replacement));
+        let last = chain.get(2).unwrap();
+        assert_eq!(last.get_previous_block_id(), Some(&replacement.id));
+        assert_ne!(last.id, old_ids[2]);
+        assert_eq!(chain.tip_id(), Some(&last.id));
+        assert_eq!(chain.validate(), Ok(()));
+
+        // The replacement has to reference the same preceding Block.
+        let tx = Transaction::new(vec![5, 6, 7, 8, 9], 2);
+        let block = Block::new(vec![tx.clone()], None);
+        assert_eq!(
+            chain.replace_block(1, block),
+            Err(ChainError::InvalidPreviousBlock(1))
+        );
+
+        let block = Block::new(vec![tx], None);
+        assert_eq!(
+            chain.replace_block(3, block),
+            Err(ChainError::UnknownHeight(3))
+        );
+    }
+
+    #[test]
+    fn fingerprint() {
+        let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        let tx_2 = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+        let tx_3 = Transaction::new(vec![0, 1, 2, 3, 4], 2);
+
+        let mut chain_1 = Chain::new(2);
+        chain_1.append(Block::new(vec![tx_1.clone()], None));
+        chain_1.append(Block::new(vec![tx_2.clone()], None));
+
+        let mut chain_2 = Chain::new(2);
+        chain_2.append(Block::new(vec![tx_1.clone()], None));
+        chain_2.append(Block::new(vec![tx_2], None));
+
+        let mut chain_3 = Chain::new(2);
+        chain_3.append(Block::new(vec![tx_1], None));
+        chain_3.append(Block::new(vec![tx_3], None));
+
+        // Identical Chains.
+        assert_eq!(chain_1.fingerprint(), chain_2.fingerprint());
+
+        // Diverging Chains.
+        assert_ne!(chain_1.fingerprint(), chain_3.fingerprint());
+        assert_ne!(chain_1.fingerprint(), Chain::new(0).fingerprint());
+    }
+
     #[test]
     fn append_multiple_blocks() {
         let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
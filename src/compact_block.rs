@@ -0,0 +1,101 @@
+use crate::{
+    block::{Block, BlockHeader},
+    mempool::Mempool,
+    utils::Keccak256,
+};
+
+/// Errors which can occur when reconstructing a [Block](crate::block::Block)
+/// from a [CompactBlock](crate::compact_block::CompactBlock).
+#[derive(Debug, PartialEq)]
+pub enum ReconstructError {
+    /// Transactions with the given ids aren't available in the Mempool.
+    MissingTransactions(Vec<Keccak256>),
+    /// The reconstructed Block id doesn't match the id in the header.
+    IdMismatch,
+}
+
+/// A [Block](crate::block::Block) which references its Transactions by id so
+/// that peers can rebuild it from their Mempool.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompactBlock {
+    /// Header of the Block.
+    pub header: BlockHeader,
+    /// Ids of the Transactions included in the Block.
+    pub tx_ids: Vec<Keccak256>,
+}
+
+impl CompactBlock {
+    /// Creates a new CompactBlock based on the given Block.
+    pub fn from_block(block: &Block) -> Self {
+        let tx_ids = block.transactions.iter().map(|tx| tx.id.clone()).collect();
+        CompactBlock {
+            header: block.header(),
+            tx_ids,
+        }
+    }
+
+    /// Reconstructs the full Block with Transactions from the given Mempool.
+    pub fn reconstruct(&self, mempool: &Mempool) -> Result<Block, ReconstructError> {
+        let mut transactions = Vec::with_capacity(self.tx_ids.len());
+        let mut missing = Vec::new();
+        for tx_id in self.tx_ids.iter() {
+            match mempool.find(tx_id) {
+                Some(tx) => transactions.push(tx.clone()),
+                None => missing.push(tx_id.clone()),
+            }
+        }
+        if !missing.is_empty() {
+            return Err(ReconstructError::MissingTransactions(missing));
+        }
+
+        let block = Block::with_extra_data(
+            transactions,
+            self.header.prev_block_id.clone(),
+            self.header.extra_data.clone(),
+        );
+        if block.id != self.header.id {
+            return Err(ReconstructError::IdMismatch);
+        }
+        Ok(block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::Transaction;
+
+    #[test]
+    fn reconstruct() {
+        let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        let tx_2 = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+        let block = Block::new(vec![tx_1.clone(), tx_2.clone()], Some([1; 32].into()));
+
+        let mut mempool = Mempool::new();
+        mempool.insert(tx_1.id.clone(), tx_1, 1);
+        mempool.insert(tx_2.id.clone(), tx_2, 1);
+
+        let compact_block = CompactBlock::from_block(&block);
+        assert_eq!(compact_block.header, block.header());
+        assert_eq!(compact_block.reconstruct(&mempool), Ok(block));
+    }
+
+    #[test]
+    fn reconstruct_missing_transactions() {
+        let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        let tx_2 = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+        let tx_3 = Transaction::new(vec![0, 1, 2, 3, 4], 2);
+        let block = Block::new(vec![tx_1.clone(), tx_2.clone(), tx_3.clone()], None);
+
+        let mut mempool = Mempool::new();
+        mempool.insert(tx_1.id.clone(), tx_1, 1);
+
+        let compact_block = CompactBlock::from_block(&block);
+        assert_eq!(
+            compact_block.reconstruct(&mempool),
+            Err(ReconstructError::MissingTransactions(vec![
+                tx_2.id, tx_3.id
+            ]))
+        );
+    }
+}
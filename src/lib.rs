@@ -7,9 +7,16 @@ extern crate sha3;
 
 pub mod block;
 pub mod chain;
+pub mod compact_block;
 pub mod mempool;
+pub mod merkle;
 pub mod node;
 pub mod snowball;
 pub mod transaction;
 
 mod utils;
+
+pub use utils::{decode_address, encode_address, AddressError, Hash256};
+
+#[cfg(test)]
+mod testing;
@@ -1,30 +1,383 @@
-use std::collections::BTreeMap;
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs, io,
+    path::Path,
+};
 
-use crate::{transaction::Transaction, utils::Keccak256};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    transaction::Transaction,
+    utils::{Keccak256, Sender},
+};
+
+/// Errors which can occur when persisting a [Mempool](crate::mempool::Mempool).
+#[derive(Debug, PartialEq)]
+pub enum MempoolError {
+    /// The file couldn't be read or written.
+    Io(io::ErrorKind),
+    /// The stored data couldn't be encoded or decoded.
+    InvalidEncoding,
+    /// The file uses a format version which isn't supported.
+    UnsupportedVersion(u16),
+}
+
+/// Tag which prefixes the files written by [Mempool::save](crate::mempool::Mempool::save).
+/// Files without it use the legacy format whose Entries don't include a sequence.
+const FILE_MAGIC: &[u8] = b"anova/mempool";
+
+/// Version of the format used by [Mempool::save](crate::mempool::Mempool::save).
+const FILE_VERSION: u16 = 1;
+
+/// Summary of the Transactions pending in a [Mempool](crate::mempool::Mempool).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MempoolStats {
+    /// Number of pending Transactions.
+    pub count: usize,
+    /// Cumulative size of the pending Transactions in bytes.
+    pub total_bytes: usize,
+}
+
+/// A pending Transaction alongside the data the Mempool tracks for it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// The pending Transaction.
+    pub transaction: Transaction,
+    /// Chain height at which the Transaction was inserted.
+    pub inserted_at_height: u64,
+    /// Position of the Transaction in the order in which Transactions were inserted.
+    pub sequence: u64,
+}
+
+/// Storage which holds the [Entries](crate::mempool::Entry) of a
+/// [Mempool](crate::mempool::Mempool) by their index.
+pub trait MempoolStore {
+    /// Inserts an Entry and returns the Entry which was previously stored at the index.
+    fn insert(&mut self, index: Keccak256, entry: Entry) -> Option<Entry>;
+
+    /// Removes the Entry with the given index.
+    fn remove(&mut self, index: &Keccak256) -> Option<Entry>;
+
+    /// Returns a reference to the Entry with the given index.
+    fn get(&self, index: &Keccak256) -> Option<&Entry>;
+
+    /// Returns an iterator over all Entries ordered by their index.
+    fn iter(&self) -> Box<dyn Iterator<Item = (&Keccak256, &Entry)> + '_>;
+
+    /// Returns the number of Entries.
+    fn len(&self) -> usize;
+
+    /// Returns whether the store doesn't hold any Entries.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all Entries and returns them ordered by their index.
+    fn drain(&mut self) -> Vec<(Keccak256, Entry)> {
+        let indexes: Vec<Keccak256> = self.iter().map(|(index, _)| index.clone()).collect();
+        indexes
+            .into_iter()
+            .filter_map(|index| self.remove(&index).map(|entry| (index, entry)))
+            .collect()
+    }
+}
+
+impl MempoolStore for BTreeMap<Keccak256, Entry> {
+    fn insert(&mut self, index: Keccak256, entry: Entry) -> Option<Entry> {
+        BTreeMap::insert(self, index, entry)
+    }
+
+    fn remove(&mut self, index: &Keccak256) -> Option<Entry> {
+        BTreeMap::remove(self, index)
+    }
+
+    fn get(&self, index: &Keccak256) -> Option<&Entry> {
+        BTreeMap::get(self, index)
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = (&Keccak256, &Entry)> + '_> {
+        Box::new(BTreeMap::iter(self))
+    }
+
+    fn len(&self) -> usize {
+        BTreeMap::len(self)
+    }
+
+    fn drain(&mut self) -> Vec<(Keccak256, Entry)> {
+        std::mem::take(self).into_iter().collect()
+    }
+}
+
+/// Function used to order the Transactions returned by the Mempool.
+pub type Comparator = Box<dyn Fn(&Transaction, &Transaction) -> Ordering>;
 
 /// A pool that stores pending [Transactions](crate::transaction::Transaction) in memory.
-pub struct Mempool(BTreeMap<Keccak256, Transaction>);
+pub struct Mempool<S = BTreeMap<Keccak256, Entry>> {
+    /// Pending Transactions by their index.
+    entries: S,
+    /// Maximum number of pending Transactions.
+    capacity: Option<usize>,
+    /// Maximum cumulative size of the pending Transactions in bytes.
+    max_bytes: Option<usize>,
+    /// Cumulative size of the pending Transactions in bytes.
+    size_bytes: usize,
+    /// Function called with every Transaction evicted from the Mempool.
+    eviction_hook: Option<Box<dyn FnMut(&Transaction)>>,
+    /// Function used to order the Transactions returned by the Mempool.
+    comparator: Option<Comparator>,
+    /// Sequence assigned to the next inserted Transaction.
+    next_sequence: u64,
+}
 
 impl Mempool {
     /// Creates a new Mempool.
     pub fn new() -> Self {
-        let mempool = BTreeMap::new();
-        Mempool(mempool)
+        Mempool::with_store(BTreeMap::new())
+    }
+}
+
+impl<S: MempoolStore> Mempool<S> {
+    /// Creates a new Mempool which keeps its pending Transactions in the given store.
+    pub fn with_store(store: S) -> Self {
+        let size_bytes = store
+            .iter()
+            .map(|(_, entry)| Self::size_of(&entry.transaction))
+            .sum();
+        let next_sequence = store
+            .iter()
+            .map(|(_, entry)| entry.sequence + 1)
+            .max()
+            .unwrap_or(0);
+        Mempool {
+            entries: store,
+            capacity: None,
+            max_bytes: None,
+            size_bytes,
+            eviction_hook: None,
+            comparator: None,
+            next_sequence,
+        }
+    }
+
+    /// Returns Transactions in the order defined by the given comparator rather
+    /// than by their index.
+    pub fn with_comparator(mut self, comparator: Comparator) -> Self {
+        self.comparator = Some(comparator);
+        self
+    }
+
+    /// Limits the number of pending Transactions to `capacity`. The Transaction
+    /// with the lowest priority is evicted when a new one doesn't fit. Can be
+    /// combined with a byte limit.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
     }
 
-    /// Insert a new Transaction into the Mempool.
-    pub fn insert(&mut self, index: Keccak256, transaction: Transaction) {
-        self.0.insert(index, transaction);
+    /// Limits the cumulative size of the pending Transactions to `max_bytes`.
+    /// The Transactions with the lowest priority are evicted until a new one fits.
+    /// Can be combined with a capacity.
+    pub fn with_byte_limit(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Sets the function which is called with every Transaction evicted because
+    /// the Mempool reached its capacity. Explicitly removed Transactions aren't
+    /// passed to the function.
+    pub fn set_eviction_hook(&mut self, hook: Box<dyn FnMut(&Transaction)>) {
+        self.eviction_hook = Some(hook);
+    }
+
+    /// Inserts the pending Transactions stored in the file at the given path in
+    /// the order in which they were inserted originally. Transactions whose id
+    /// doesn't match their contents are dropped and the limits of the Mempool apply.
+    pub fn load<P: AsRef<Path>>(mut self, path: P) -> Result<Self, MempoolError> {
+        let data = fs::read(path).map_err(|error| MempoolError::Io(error.kind()))?;
+        let mut entries = decode_entries(&data)?;
+        entries.sort_by_key(|(_, entry)| entry.sequence);
+        for (index, entry) in entries.into_iter() {
+            if entry.transaction.validate() {
+                self.insert(index, entry.transaction, entry.inserted_at_height);
+            }
+        }
+        Ok(self)
+    }
+
+    /// Writes all pending Transactions to the file at the given path.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), MempoolError> {
+        // A list of pairs is encoded just like a map of indexes to entries.
+        let entries: Vec<(&Keccak256, &Entry)> = self.entries.iter().collect();
+        let encoded = bincode::serialize(&(FILE_VERSION, entries))
+            .map_err(|_| MempoolError::InvalidEncoding)?;
+        let data = [FILE_MAGIC, &encoded[..]].concat();
+        fs::write(path, data).map_err(|error| MempoolError::Io(error.kind()))
+    }
+
+    /// Insert a new Transaction into the Mempool, recording the Chain height at
+    /// which it was inserted. A Transaction which is larger than the byte limit or
+    /// inserted into a Mempool with a capacity of 0 isn't inserted. Returns the
+    /// Transaction which was previously stored at the index (if any).
+    pub fn insert(
+        &mut self,
+        index: Keccak256,
+        transaction: Transaction,
+        inserted_at_height: u64,
+    ) -> Option<Transaction> {
+        let size = Self::size_of(&transaction);
+        if self.max_bytes.map_or(false, |max_bytes| size > max_bytes) || self.capacity == Some(0) {
+            return None;
+        }
+
+        // Replace the Transaction which is currently stored at the index.
+        let previous = self.remove_entry(&index).map(|entry| entry.transaction);
+
+        if let Some(capacity) = self.capacity {
+            if self.entries.len() >= capacity {
+                self.evict_lowest_priority();
+            }
+        }
+        if let Some(max_bytes) = self.max_bytes {
+            while self.size_bytes + size > max_bytes {
+                self.evict_lowest_priority();
+            }
+        }
+
+        let entry = Entry {
+            transaction,
+            inserted_at_height,
+            sequence: self.next_sequence,
+        };
+        self.next_sequence += 1;
+        self.size_bytes += size;
+        self.entries.insert(index, entry);
+        previous
+    }
+
+    /// Inserts the Transactions of the given Mempool whose index isn't taken yet
+    /// in the order in which they were inserted into it. Transactions don't carry a
+    /// fee, so an existing Transaction always wins over an incoming one with the
+    /// same index. Existing Transactions are kept, so Transactions which don't fit into this Mempool without evicting one
+    /// are skipped. Returns the number of Transactions which were added.
+    pub fn merge<T: MempoolStore>(&mut self, mut other: Mempool<T>) -> usize {
+        let mut entries = other.entries.drain();
+        entries.sort_by_key(|(_, entry)| entry.sequence);
+
+        let mut added = 0;
+        for (index, entry) in entries.into_iter() {
+            if self.entries.get(&index).is_some() || !self.fits(&entry.transaction) {
+                continue;
+            }
+            self.insert(index, entry.transaction, entry.inserted_at_height);
+            added += 1;
+        }
+        added
+    }
+
+    /// Returns a reference to the Transaction with the given index.
+    pub fn get(&self, index: &Keccak256) -> Option<&Transaction> {
+        self.entries.get(index).map(|entry| &entry.transaction)
+    }
+
+    /// Returns a reference to the Transaction with the given Transaction id.
+    pub fn find(&self, tx_id: &Keccak256) -> Option<&Transaction> {
+        self.transactions().find(|tx| &tx.id == tx_id)
+    }
+
+    /// Returns an iterator over references to the pending Transactions.
+    pub fn transactions(&self) -> impl Iterator<Item = &Transaction> + '_ {
+        self.entries.iter().map(|(_, entry)| &entry.transaction)
+    }
+
+    /// Returns the indexes of pending Transactions which share the sender and
+    /// nonce of the given Transaction.
+    pub fn conflicts(&self, transaction: &Transaction) -> Vec<Keccak256> {
+        self.entries
+            .iter()
+            .filter(|(_, entry)| {
+                entry.transaction.sender() == transaction.sender()
+                    && entry.transaction.nonce() == transaction.nonce()
+            })
+            .map(|(index, _)| index.clone())
+            .collect()
+    }
+
+    /// Returns the pending Transactions grouped by their sender. The Transactions
+    /// of every sender are sorted by their nonce.
+    pub fn grouped_by_sender(&self) -> HashMap<Sender, Vec<Transaction>> {
+        let mut groups: HashMap<Sender, Vec<Transaction>> = HashMap::new();
+        for (_, entry) in self.entries.iter() {
+            let tx = &entry.transaction;
+            groups
+                .entry(tx.sender().clone())
+                .or_default()
+                .push(tx.clone());
+        }
+        for transactions in groups.values_mut() {
+            transactions.sort_by_key(|tx| tx.nonce());
+        }
+        groups
+    }
+
+    /// Returns the distinct senders of the pending Transactions in ascending order.
+    pub fn pending_senders(&self) -> Vec<Sender> {
+        let senders: BTreeSet<&Sender> = self.transactions().map(|tx| tx.sender()).collect();
+        senders.into_iter().cloned().collect()
+    }
+
+    /// Return all Transactions which affect the given account. Transactions don't
+    /// have a recipient yet, so only the sender is taken into account.
+    pub fn transactions_affecting(&self, account: &[u8]) -> Vec<Transaction> {
+        self.transactions()
+            .filter(|tx| tx.sender().as_slice() == account)
+            .cloned()
+            .collect()
     }
 
     /// Remove all Transactions in the Mempool.
     pub fn clear(&mut self) {
-        self.0.clear();
+        self.entries.drain();
+        self.size_bytes = 0;
+    }
+
+    /// Remove all Transactions from the Mempool and return them alongside the
+    /// Chain height at which they were inserted. The Transactions are returned in
+    /// the order in which they were inserted, so re-inserting them keeps that order.
+    pub fn drain(&mut self) -> Vec<(Transaction, u64)> {
+        self.size_bytes = 0;
+        let mut entries = self.entries.drain();
+        entries.sort_by_key(|(_, entry)| entry.sequence);
+        entries
+            .into_iter()
+            .map(|(_, entry)| (entry.transaction, entry.inserted_at_height))
+            .collect()
     }
 
     /// Returns the number of Transactions in the Mempool.
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.entries.len()
+    }
+
+    /// Returns the cumulative size of the pending Transactions in bytes.
+    pub fn size_bytes(&self) -> usize {
+        self.size_bytes
+    }
+
+    /// Returns a summary of the pending Transactions.
+    pub fn stats(&self) -> MempoolStats {
+        MempoolStats {
+            count: self.entries.len(),
+            total_bytes: self.size_bytes,
+        }
+    }
+
+    /// Returns the number of blocks the Transaction with the given index has been
+    /// pending for at the given Chain height.
+    pub fn age_of(&self, index: &Keccak256, now: u64) -> Option<u64> {
+        self.entries
+            .get(index)
+            .map(|entry| now.saturating_sub(entry.inserted_at_height))
     }
 
     /// Remove Transactions based on their indexes from the Mempool. Return the
@@ -32,96 +385,719 @@ impl Mempool {
     pub fn remove_transactions(&mut self, indexes: Vec<Keccak256>) -> usize {
         let mut removed = 0;
         for index in indexes.iter() {
-            if let Some(_) = self.0.remove(index) {
+            if let Some(_) = self.remove_entry(index) {
                 removed += 1;
             }
         }
         removed
     }
 
+    /// Remove Transactions whose nonce isn't higher than the latest finalized
+    /// nonce of their sender. Return the number of removed Transactions.
+    pub fn drop_stale_nonces(&mut self, latest_nonces: &HashMap<Sender, u64>) -> usize {
+        let stale: Vec<Keccak256> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| {
+                let tx = &entry.transaction;
+                latest_nonces
+                    .get(tx.sender())
+                    .map_or(false, |latest| tx.nonce() <= *latest)
+            })
+            .map(|(index, _)| index.clone())
+            .collect();
+        self.remove_transactions(stale)
+    }
+
+    /// Remove the Transaction with the lowest priority and pass it to the eviction
+    /// hook. That's the Transaction the comparator orders last or, without a
+    /// comparator, the Transaction which was inserted first.
+    fn evict_lowest_priority(&mut self) {
+        let lowest = match &self.comparator {
+            Some(comparator) => self
+                .entries
+                .iter()
+                .max_by(|(_, a), (_, b)| comparator(&a.transaction, &b.transaction)),
+            None => self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| (entry.inserted_at_height, entry.sequence)),
+        }
+        .map(|(index, _)| index.clone());
+
+        if let Some(entry) = lowest.and_then(|index| self.remove_entry(&index)) {
+            if let Some(hook) = self.eviction_hook.as_mut() {
+                hook(&entry.transaction);
+            }
+        }
+    }
+
+    /// Returns whether the given Transaction can be inserted without evicting a
+    /// pending Transaction.
+    fn fits(&self, transaction: &Transaction) -> bool {
+        let has_room = self
+            .capacity
+            .map_or(true, |capacity| self.entries.len() < capacity);
+        let size = self.size_bytes + Self::size_of(transaction);
+        has_room && self.max_bytes.map_or(true, |max_bytes| size <= max_bytes)
+    }
+
+    /// Remove the entry with the given index and update the cumulative size.
+    fn remove_entry(&mut self, index: &Keccak256) -> Option<Entry> {
+        let entry = self.entries.remove(index)?;
+        self.size_bytes -= Self::size_of(&entry.transaction);
+        Some(entry)
+    }
+
+    /// Returns the size of a Transactions binary representation in bytes.
+    fn size_of(transaction: &Transaction) -> usize {
+        bincode::serialized_size(transaction).unwrap() as usize
+    }
+
     /// Return all Transactions currently available in the Mempool.
     pub fn get_all_transactions(&self) -> Option<Vec<Transaction>> {
         if self.len() != 0 {
-            return Some(self.0.values().cloned().collect());
+            let mut transactions: Vec<Transaction> = self
+                .entries
+                .iter()
+                .map(|(_, entry)| entry.transaction.clone())
+                .collect();
+            if let Some(comparator) = &self.comparator {
+                transactions.sort_by(|a, b| comparator(a, b));
+            }
+            return Some(transactions);
+        }
+        None
+    }
+
+    /// Return all Transactions currently available in the Mempool in the order in
+    /// which they were inserted.
+    pub fn get_all_transactions_fifo(&self) -> Option<Vec<Transaction>> {
+        if self.len() != 0 {
+            let mut entries: Vec<&Entry> = self.entries.iter().map(|(_, entry)| entry).collect();
+            entries.sort_by_key(|entry| entry.sequence);
+            let transactions = entries
+                .into_iter()
+                .map(|entry| entry.transaction.clone())
+                .collect();
+            return Some(transactions);
         }
         None
     }
 }
 
+/// Decodes the Entries stored in a file written by [Mempool::save].
+fn decode_entries(data: &[u8]) -> Result<Vec<(Keccak256, Entry)>, MempoolError> {
+    if data.starts_with(FILE_MAGIC) {
+        let (version, entries): (u16, Vec<(Keccak256, Entry)>) =
+            bincode::deserialize(&data[FILE_MAGIC.len()..])
+                .map_err(|_| MempoolError::InvalidEncoding)?;
+        if version != FILE_VERSION {
+            return Err(MempoolError::UnsupportedVersion(version));
+        }
+        return Ok(entries);
+    }
+
+    // Legacy files store every Entry as its Transaction and insertion height. The
+    // Entries are ordered by their index, which is used as their sequence.
+    let entries: Vec<(Keccak256, (Transaction, u64))> =
+        bincode::deserialize(data).map_err(|_| MempoolError::InvalidEncoding)?;
+    let entries = entries
+        .into_iter()
+        .enumerate()
+        .map(|(sequence, (index, (transaction, inserted_at_height)))| {
+            let entry = Entry {
+                transaction,
+                inserted_at_height,
+                sequence: sequence as u64,
+            };
+            (index, entry)
+        })
+        .collect();
+    Ok(entries)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::{cell::RefCell, rc::Rc};
 
-    #[test]
-    fn new_mempool() {
-        let mempool = Mempool::new();
-        assert_eq!(mempool.0.len(), 0);
-    }
+    /// Store which keeps its Entries in a Vec sorted by their index.
+    #[derive(Default)]
+    struct VecStore(Vec<(Keccak256, Entry)>);
 
-    #[test]
-    fn insert() {
-        let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
-        let index = tx.id.clone();
+    impl MempoolStore for VecStore {
+        fn insert(&mut self, index: Keccak256, entry: Entry) -> Option<Entry> {
+            match self.0.binary_search_by(|(other, _)| other.cmp(&index)) {
+                Ok(position) => Some(std::mem::replace(&mut self.0[position].1, entry)),
+                Err(position) => {
+                    self.0.insert(position, (index, entry));
+                    None
+                }
+            }
+        }
+
+        fn remove(&mut self, index: &Keccak256) -> Option<Entry> {
+            let position = self
+                .0
+                .binary_search_by(|(other, _)| other.cmp(index))
+                .ok()?;
+            Some(self.0.remove(position).1)
+        }
 
-        let mut mempool = Mempool::new();
-        mempool.insert(index.clone(), tx.clone());
+        fn get(&self, index: &Keccak256) -> Option<&Entry> {
+            self.0
+                .iter()
+                .find(|(other, _)| other == index)
+                .map(|(_, entry)| entry)
+        }
+
+        fn iter(&self) -> Box<dyn Iterator<Item = (&Keccak256, &Entry)> + '_> {
+            Box::new(self.0.iter().map(|(index, entry)| (index, entry)))
+        }
 
-        assert_eq!(mempool.0.len(), 1);
-        assert_eq!(mempool.0.get(&index), Some(&tx));
+        fn len(&self) -> usize {
+            self.0.len()
+        }
     }
 
-    #[test]
-    fn clear() {
-        let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
-        let index = tx.id.clone();
+    /// Generates the Mempool tests for a Mempool backed by the given store.
+    macro_rules! mempool_tests {
+        ($name:ident, $store:ty) => {
+            mod $name {
+                use super::*;
 
-        let mut mempool = Mempool::new();
-        mempool.insert(index, tx);
+                /// Creates an empty Mempool backed by the store under test.
+                fn empty_mempool() -> Mempool<$store> {
+                    Mempool::with_store(<$store>::default())
+                }
 
-        mempool.clear();
-        assert_eq!(mempool.0.len(), 0);
-    }
+                #[test]
+                fn new_mempool() {
+                    let mempool = empty_mempool();
+                    assert_eq!(mempool.entries.len(), 0);
+                }
 
-    #[test]
-    fn remove_transactions() {
-        let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
-        let tx_2 = Transaction::new(vec![5, 6, 7, 8, 9], 1);
-        let tx_3 = Transaction::new(vec![0, 1, 2, 3, 4], 2);
-        let tx_1_idx = tx_1.id.clone();
-        let tx_2_idx = tx_2.id.clone();
-        let tx_3_idx = tx_3.id.clone();
+                #[test]
+                fn insert() {
+                    let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+                    let index = tx.id.clone();
 
-        let mut mempool = Mempool::new();
+                    let mut mempool = empty_mempool();
+                    assert_eq!(mempool.insert(index.clone(), tx.clone(), 1), None);
 
-        let removed = mempool.remove_transactions(vec![tx_2_idx.clone()]);
-        assert_eq!(removed, 0);
+                    assert_eq!(mempool.entries.len(), 1);
+                    assert_eq!(mempool.get(&index), Some(&tx));
 
-        mempool.insert(tx_1_idx.clone(), tx_1.clone());
-        mempool.insert(tx_2_idx.clone(), tx_2.clone());
-        mempool.insert(tx_3_idx.clone(), tx_3.clone());
+                    // Inserting at the same index returns the previous Transaction.
+                    let other = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+                    assert_eq!(mempool.insert(index.clone(), other.clone(), 2), Some(tx));
+                    assert_eq!(mempool.entries.len(), 1);
+                    assert_eq!(mempool.get(&index), Some(&other));
+                }
 
-        let removed = mempool.remove_transactions(vec![tx_1_idx, tx_3_idx]);
+                #[test]
+                fn merge() {
+                    let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+                    let tx_2 = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+                    let tx_3 = Transaction::new(vec![0, 1, 2, 3, 4], 2);
+                    let replacement = Transaction::new(vec![5, 6, 7, 8, 9], 2);
 
-        assert_eq!(removed, 2);
-        assert_eq!(mempool.0.len(), 1);
-        assert_eq!(mempool.0.get(&tx_2_idx), Some(&tx_2));
-    }
+                    let mut mempool = empty_mempool();
+                    mempool.insert(tx_1.id.clone(), tx_1.clone(), 1);
+                    mempool.insert(tx_2.id.clone(), tx_2.clone(), 1);
+
+                    let mut other = empty_mempool();
+                    other.insert(tx_1.id.clone(), tx_1.clone(), 2);
+                    other.insert(tx_2.id.clone(), replacement, 2);
+                    other.insert(tx_3.id.clone(), tx_3.clone(), 2);
+
+                    // Only the Transaction with a new index is added.
+                    assert_eq!(mempool.merge(other), 1);
+                    assert_eq!(mempool.len(), 3);
+                    assert_eq!(mempool.get(&tx_2.id), Some(&tx_2));
+                    assert_eq!(mempool.get(&tx_3.id), Some(&tx_3));
+                    assert_eq!(mempool.age_of(&tx_1.id, 2), Some(1));
+
+                    // Transactions are skipped once the Mempool is full, so existing
+                    // Transactions aren't evicted.
+                    let mut mempool = empty_mempool().with_capacity(2);
+                    mempool.insert(tx_1.id.clone(), tx_1.clone(), 1);
+                    let mut other = empty_mempool();
+                    other.insert(tx_2.id.clone(), tx_2.clone(), 1);
+                    other.insert(tx_3.id.clone(), tx_3.clone(), 1);
+                    assert_eq!(mempool.merge(other), 1);
+                    assert_eq!(mempool.len(), 2);
+                    assert_eq!(mempool.get(&tx_1.id), Some(&tx_1));
+                    assert_eq!(mempool.get(&tx_2.id), Some(&tx_2));
+                    assert_eq!(mempool.get(&tx_3.id), None);
+
+                    // The byte limit is respected the same way. A Transaction which
+                    // doesn't fit doesn't keep smaller ones from being added.
+                    let large = Transaction::new(vec![1; 20], 1);
+                    let mut mempool = empty_mempool().with_byte_limit(130);
+                    mempool.insert(tx_1.id.clone(), tx_1.clone(), 1);
+                    let mut other = empty_mempool();
+                    other.insert(large.id.clone(), large.clone(), 1);
+                    other.insert(tx_2.id.clone(), tx_2.clone(), 1);
+                    other.insert(tx_3.id.clone(), tx_3, 1);
+                    assert_eq!(mempool.merge(other), 1);
+                    assert_eq!(mempool.len(), 2);
+                    assert_eq!(mempool.get(&tx_1.id), Some(&tx_1));
+                    assert_eq!(mempool.get(&tx_2.id), Some(&tx_2));
+                    assert_eq!(mempool.get(&large.id), None);
+                }
+
+                #[test]
+                fn find() {
+                    let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+
+                    let mut mempool = empty_mempool();
+                    mempool.insert([5; 32].into(), tx.clone(), 1);
+
+                    assert_eq!(mempool.find(&tx.id), Some(&tx));
+                    assert_eq!(mempool.find(&[5; 32].into()), None);
+                }
+
+                #[test]
+                fn transactions() {
+                    let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+                    let tx_2 = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+
+                    let mut mempool = empty_mempool();
+                    assert_eq!(mempool.transactions().count(), 0);
+
+                    mempool.insert(tx_1.id.clone(), tx_1.clone(), 1);
+                    mempool.insert(tx_2.id.clone(), tx_2.clone(), 1);
+                    let transactions: Vec<&Transaction> = mempool.transactions().collect();
+                    assert_eq!(transactions, vec![&tx_2, &tx_1]);
+                }
+
+                #[test]
+                fn conflicts() {
+                    let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+
+                    let mut mempool = empty_mempool();
+                    mempool.insert([5; 32].into(), tx, 1);
+
+                    let same_nonce = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+                    assert_eq!(
+                        mempool.conflicts(&same_nonce),
+                        vec![Keccak256::from([5; 32])]
+                    );
+
+                    let other_nonce = Transaction::new(vec![0, 1, 2, 3, 4], 2);
+                    assert!(mempool.conflicts(&other_nonce).is_empty());
+                }
+
+                #[test]
+                fn save_and_load() {
+                    let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+                    let tx_2 = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+                    let mut tx_3 = Transaction::new(vec![0, 1, 2, 3, 4], 2);
+                    tx_3.id = [0; 32].into();
+
+                    let mut mempool = empty_mempool();
+                    mempool.insert([1; 32].into(), tx_1.clone(), 1);
+                    mempool.insert([2; 32].into(), tx_2.clone(), 3);
+                    mempool.insert([3; 32].into(), tx_3, 3);
+
+                    let path = std::env::temp_dir()
+                        .join(concat!("anova_mempool_save_and_load_", stringify!($name)));
+                    mempool.save(&path).unwrap();
+                    let loaded = empty_mempool().load(&path).unwrap();
+                    std::fs::remove_file(&path).unwrap();
+
+                    // The tampered Transaction is dropped while the others keep their indexes.
+                    assert_eq!(loaded.len(), 2);
+                    assert_eq!(loaded.get(&[1; 32].into()), Some(&tx_1));
+                    assert_eq!(loaded.get(&[2; 32].into()), Some(&tx_2));
+                    assert_eq!(loaded.age_of(&[2; 32].into(), 5), Some(2));
+
+                    // Transactions inserted after loading are ordered after the loaded ones.
+                    assert_eq!(loaded.next_sequence, 2);
+                }
+
+                #[test]
+                fn load_legacy_file() {
+                    let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+                    let tx_2 = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+
+                    // Legacy files don't include a version nor the sequence of the Entries.
+                    let entries: Vec<(Keccak256, (Transaction, u64))> = vec![
+                        ([1; 32].into(), (tx_2.clone(), 3)),
+                        ([2; 32].into(), (tx_1.clone(), 1)),
+                    ];
+                    let path = std::env::temp_dir().join(concat!(
+                        "anova_mempool_load_legacy_file_",
+                        stringify!($name)
+                    ));
+                    std::fs::write(&path, bincode::serialize(&entries).unwrap()).unwrap();
+                    let loaded = empty_mempool().load(&path).unwrap();
+                    std::fs::remove_file(&path).unwrap();
+
+                    // Sequences are assigned in the order of the indexes.
+                    assert_eq!(loaded.len(), 2);
+                    assert_eq!(loaded.age_of(&[1; 32].into(), 5), Some(2));
+                    assert_eq!(loaded.get_all_transactions_fifo(), Some(vec![tx_2, tx_1]));
+                    assert_eq!(loaded.next_sequence, 2);
+                }
+
+                #[test]
+                fn load_unsupported_version() {
+                    let entries: Vec<(Keccak256, Entry)> = Vec::new();
+                    let encoded = bincode::serialize(&(FILE_VERSION + 1, entries)).unwrap();
+                    let path = std::env::temp_dir().join(concat!(
+                        "anova_mempool_load_unsupported_version_",
+                        stringify!($name)
+                    ));
+                    std::fs::write(&path, [FILE_MAGIC, &encoded[..]].concat()).unwrap();
+                    let loaded = empty_mempool().load(&path);
+                    std::fs::remove_file(&path).unwrap();
+
+                    assert_eq!(
+                        loaded.err(),
+                        Some(MempoolError::UnsupportedVersion(FILE_VERSION + 1))
+                    );
+                }
+
+                #[test]
+                fn load_missing_file() {
+                    let path = std::env::temp_dir().join(concat!(
+                        "anova_mempool_load_missing_file_",
+                        stringify!($name)
+                    ));
+
+                    let loaded = empty_mempool().load(&path);
+                    assert_eq!(
+                        loaded.err(),
+                        Some(MempoolError::Io(io::ErrorKind::NotFound))
+                    );
+                }
+
+                #[test]
+                fn grouped_by_sender() {
+                    let mut mempool = empty_mempool();
+                    for nonce in [2, 1].iter() {
+                        let tx = Transaction::new(vec![0, 1, 2, 3, 4], *nonce);
+                        mempool.insert(tx.id.clone(), tx, 1);
+                    }
+                    for nonce in [7, 5, 6].iter() {
+                        let tx = Transaction::new(vec![5, 6, 7, 8, 9], *nonce);
+                        mempool.insert(tx.id.clone(), tx, 1);
+                    }
+
+                    let groups = mempool.grouped_by_sender();
+                    assert_eq!(groups.len(), 2);
+                    let nonces = |sender: Sender| -> Vec<u64> {
+                        groups[&sender].iter().map(|tx| tx.nonce()).collect()
+                    };
+                    assert_eq!(nonces(vec![0, 1, 2, 3, 4]), vec![1, 2]);
+                    assert_eq!(nonces(vec![5, 6, 7, 8, 9]), vec![5, 6, 7]);
+                }
+
+                #[test]
+                fn pending_senders() {
+                    let mut mempool = empty_mempool();
+                    assert_eq!(mempool.pending_senders(), Vec::<Sender>::new());
 
-    #[test]
-    fn get_all_transactions() {
-        let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
-        let tx_2 = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+                    for (sender, nonce) in
+                        [(vec![5, 6, 7, 8, 9], 1), (vec![0, 1, 2, 3, 4], 1)].iter()
+                    {
+                        let tx = Transaction::new(sender.clone(), *nonce);
+                        mempool.insert(tx.id.clone(), tx, 1);
+                    }
+                    let tx = Transaction::new(vec![5, 6, 7, 8, 9], 2);
+                    mempool.insert(tx.id.clone(), tx, 1);
 
-        let mut mempool = Mempool::new();
+                    assert_eq!(
+                        mempool.pending_senders(),
+                        vec![vec![0, 1, 2, 3, 4], vec![5, 6, 7, 8, 9]]
+                    );
+                }
 
-        let transactions = mempool.get_all_transactions();
-        assert_eq!(transactions, None);
+                #[test]
+                fn transactions_affecting() {
+                    let mut mempool = empty_mempool();
+                    let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+                    let tx_2 = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+                    let tx_3 = Transaction::new(vec![0, 1, 2, 3, 4], 2);
+                    for tx in [tx_1.clone(), tx_2.clone(), tx_3.clone()].iter() {
+                        mempool.insert(tx.id.clone(), tx.clone(), 1);
+                    }
 
-        mempool.insert(tx_1.id.clone(), tx_1.clone());
-        mempool.insert(tx_2.id.clone(), tx_2.clone());
-        let expected = vec![tx_2, tx_1];
+                    let mut affecting = mempool.transactions_affecting(&[0, 1, 2, 3, 4]);
+                    affecting.sort_by_key(|tx| tx.nonce());
+                    assert_eq!(affecting, vec![tx_1, tx_3]);
+                    assert_eq!(mempool.transactions_affecting(&[5, 6, 7, 8, 9]), vec![tx_2]);
+                    assert!(mempool.transactions_affecting(&[1, 2, 3]).is_empty());
+                }
 
-        let transactions = mempool.get_all_transactions();
-        assert_eq!(transactions, Some(expected));
+                #[test]
+                fn clear() {
+                    let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+                    let index = tx.id.clone();
+
+                    let mut mempool = empty_mempool();
+                    mempool.insert(index, tx, 1);
+
+                    mempool.clear();
+                    assert_eq!(mempool.entries.len(), 0);
+                }
+
+                #[test]
+                fn drain() {
+                    let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+                    let tx_2 = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+
+                    let mut mempool = empty_mempool();
+                    mempool.insert(tx_1.id.clone(), tx_1.clone(), 1);
+                    mempool.insert(tx_2.id.clone(), tx_2.clone(), 3);
+
+                    let drained = mempool.drain();
+                    assert_eq!(drained, vec![(tx_1, 1), (tx_2, 3)]);
+                    assert_eq!(mempool.entries.len(), 0);
+                }
+
+                #[test]
+                fn stats() {
+                    let mut mempool = empty_mempool();
+                    let expected = MempoolStats {
+                        count: 0,
+                        total_bytes: 0,
+                    };
+                    assert_eq!(mempool.stats(), expected);
+
+                    let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+                    let tx_2 = Transaction::new(vec![1; 50], 1);
+                    mempool.insert(tx_1.id.clone(), tx_1, 1);
+                    mempool.insert(tx_2.id.clone(), tx_2, 1);
+                    let expected = MempoolStats {
+                        count: 2,
+                        total_bytes: 167,
+                    };
+                    assert_eq!(mempool.stats(), expected);
+                }
+
+                #[test]
+                fn age_of() {
+                    let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+                    let tx_2 = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+
+                    let mut mempool = empty_mempool();
+                    mempool.insert(tx_1.id.clone(), tx_1.clone(), 1);
+                    mempool.insert(tx_2.id.clone(), tx_2.clone(), 3);
+
+                    assert_eq!(mempool.age_of(&tx_1.id, 5), Some(4));
+                    assert_eq!(mempool.age_of(&tx_2.id, 5), Some(2));
+                    assert_eq!(mempool.age_of(&[0; 32].into(), 5), None);
+                }
+
+                #[test]
+                fn eviction_hook() {
+                    let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+                    let tx_2 = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+
+                    let evicted = Rc::new(RefCell::new(Vec::new()));
+                    let hook_evicted = evicted.clone();
+
+                    let mut mempool = empty_mempool().with_capacity(1);
+                    mempool.set_eviction_hook(Box::new(move |tx: &Transaction| {
+                        hook_evicted.borrow_mut().push(tx.clone())
+                    }));
+
+                    mempool.insert(tx_1.id.clone(), tx_1.clone(), 1);
+                    assert!(evicted.borrow().is_empty());
+
+                    // Inserting a second Transaction evicts the first one.
+                    mempool.insert(tx_2.id.clone(), tx_2.clone(), 2);
+                    assert_eq!(*evicted.borrow(), vec![tx_1]);
+                    assert_eq!(mempool.entries.len(), 1);
+                    assert_eq!(mempool.get(&tx_2.id), Some(&tx_2));
+
+                    // Explicitly removed Transactions don't fire the hook.
+                    mempool.remove_transactions(vec![tx_2.id.clone()]);
+                    assert_eq!(evicted.borrow().len(), 1);
+                }
+
+                #[test]
+                fn with_byte_limit() {
+                    // Transactions with a 5 byte sender are encoded in 61 bytes while a 50
+                    // byte sender results in 106 bytes.
+                    let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+                    let tx_2 = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+                    let tx_3 = Transaction::new(vec![1; 50], 1);
+
+                    let mut mempool = empty_mempool().with_byte_limit(130);
+                    mempool.insert(tx_1.id.clone(), tx_1.clone(), 1);
+                    mempool.insert(tx_2.id.clone(), tx_2.clone(), 2);
+                    assert_eq!(mempool.size_bytes(), 122);
+
+                    // Both smaller Transactions are evicted to make room for the larger one.
+                    mempool.insert(tx_3.id.clone(), tx_3.clone(), 3);
+                    assert_eq!(mempool.len(), 1);
+                    assert_eq!(mempool.size_bytes(), 106);
+                    assert_eq!(mempool.get(&tx_3.id), Some(&tx_3));
+
+                    // Transactions which exceed the byte limit on their own aren't inserted.
+                    let mut mempool = empty_mempool().with_byte_limit(100);
+                    mempool.insert(tx_3.id.clone(), tx_3, 1);
+                    assert_eq!(mempool.len(), 0);
+                    assert_eq!(mempool.size_bytes(), 0);
+
+                    // The byte limit can be combined with a capacity.
+                    let mut mempool = empty_mempool().with_capacity(1).with_byte_limit(1000);
+                    mempool.insert(tx_1.id.clone(), tx_1, 1);
+                    mempool.insert(tx_2.id.clone(), tx_2.clone(), 2);
+                    assert_eq!(mempool.len(), 1);
+                    assert_eq!(mempool.size_bytes(), 61);
+                    assert_eq!(mempool.get(&tx_2.id), Some(&tx_2));
+                }
+
+                #[test]
+                fn eviction_order() {
+                    let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+                    let tx_2 = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+                    let tx_3 = Transaction::new(vec![0, 1, 2, 3, 4], 2);
+
+                    // Transactions inserted at the same height are evicted in the
+                    // order in which they were inserted, regardless of their index.
+                    let mut mempool = empty_mempool().with_capacity(2);
+                    mempool.insert(tx_1.id.clone(), tx_1.clone(), 1);
+                    mempool.insert(tx_2.id.clone(), tx_2.clone(), 1);
+                    mempool.insert(tx_3.id.clone(), tx_3.clone(), 1);
+                    assert_eq!(mempool.get(&tx_1.id), None);
+                    assert_eq!(mempool.len(), 2);
+
+                    // The Transaction the comparator orders last is evicted.
+                    let by_nonce = |a: &Transaction, b: &Transaction| a.nonce().cmp(&b.nonce());
+                    let mut mempool = empty_mempool()
+                        .with_comparator(Box::new(by_nonce))
+                        .with_capacity(2);
+                    mempool.insert(tx_1.id.clone(), tx_1.clone(), 1);
+                    mempool.insert(tx_3.id.clone(), tx_3.clone(), 2);
+                    mempool.insert(tx_2.id.clone(), tx_2.clone(), 3);
+                    assert_eq!(mempool.get(&tx_3.id), None);
+                    assert_eq!(mempool.get(&tx_1.id), Some(&tx_1));
+                    assert_eq!(mempool.get(&tx_2.id), Some(&tx_2));
+
+                    // A Mempool with a capacity of 0 doesn't store any Transaction.
+                    let mut mempool = empty_mempool().with_capacity(0);
+                    assert_eq!(mempool.insert(tx_1.id.clone(), tx_1, 1), None);
+                    assert_eq!(mempool.len(), 0);
+                    assert_eq!(mempool.size_bytes(), 0);
+                }
+
+                #[test]
+                fn remove_transactions() {
+                    let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+                    let tx_2 = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+                    let tx_3 = Transaction::new(vec![0, 1, 2, 3, 4], 2);
+                    let tx_1_idx = tx_1.id.clone();
+                    let tx_2_idx = tx_2.id.clone();
+                    let tx_3_idx = tx_3.id.clone();
+
+                    let mut mempool = empty_mempool();
+
+                    let removed = mempool.remove_transactions(vec![tx_2_idx.clone()]);
+                    assert_eq!(removed, 0);
+
+                    mempool.insert(tx_1_idx.clone(), tx_1.clone(), 1);
+                    mempool.insert(tx_2_idx.clone(), tx_2.clone(), 1);
+                    mempool.insert(tx_3_idx.clone(), tx_3.clone(), 1);
+
+                    let removed = mempool.remove_transactions(vec![tx_1_idx, tx_3_idx]);
+
+                    assert_eq!(removed, 2);
+                    assert_eq!(mempool.entries.len(), 1);
+                    assert_eq!(mempool.get(&tx_2_idx), Some(&tx_2));
+                }
+
+                #[test]
+                fn drop_stale_nonces() {
+                    let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 2);
+                    let tx_2 = Transaction::new(vec![0, 1, 2, 3, 4], 4);
+                    let tx_3 = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+
+                    let mut mempool = empty_mempool();
+                    mempool.insert(tx_1.id.clone(), tx_1.clone(), 1);
+                    mempool.insert(tx_2.id.clone(), tx_2.clone(), 1);
+                    mempool.insert(tx_3.id.clone(), tx_3.clone(), 1);
+
+                    let mut latest_nonces = HashMap::new();
+                    latest_nonces.insert(vec![0, 1, 2, 3, 4], 3);
+
+                    assert_eq!(mempool.drop_stale_nonces(&latest_nonces), 1);
+                    assert_eq!(mempool.get(&tx_1.id), None);
+                    assert_eq!(mempool.get(&tx_2.id), Some(&tx_2));
+                    assert_eq!(mempool.get(&tx_3.id), Some(&tx_3));
+                }
+
+                #[test]
+                fn get_all_transactions() {
+                    let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+                    let tx_2 = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+
+                    let mut mempool = empty_mempool();
+
+                    let transactions = mempool.get_all_transactions();
+                    assert_eq!(transactions, None);
+
+                    mempool.insert(tx_1.id.clone(), tx_1.clone(), 1);
+                    mempool.insert(tx_2.id.clone(), tx_2.clone(), 1);
+                    let expected = vec![tx_2, tx_1];
+
+                    let transactions = mempool.get_all_transactions();
+                    assert_eq!(transactions, Some(expected));
+                }
+
+                #[test]
+                fn get_all_transactions_fifo() {
+                    let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+                    let tx_2 = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+                    let tx_3 = Transaction::new(vec![0, 1, 2, 3, 4], 2);
+
+                    let mut mempool = empty_mempool();
+                    assert_eq!(mempool.get_all_transactions_fifo(), None);
+
+                    mempool.insert(tx_1.id.clone(), tx_1.clone(), 1);
+                    mempool.insert(tx_2.id.clone(), tx_2.clone(), 1);
+                    mempool.insert(tx_3.id.clone(), tx_3.clone(), 1);
+
+                    // The indexes sort differently than the Transactions were inserted.
+                    let by_index = vec![tx_2.clone(), tx_3.clone(), tx_1.clone()];
+                    assert_eq!(mempool.get_all_transactions(), Some(by_index));
+
+                    let expected = vec![tx_1, tx_2, tx_3];
+                    assert_eq!(mempool.get_all_transactions_fifo(), Some(expected.clone()));
+
+                    // Draining and re-inserting the Transactions keeps their order.
+                    for (tx, inserted_at_height) in mempool.drain() {
+                        mempool.insert(tx.id.clone(), tx, inserted_at_height);
+                    }
+                    assert_eq!(mempool.get_all_transactions_fifo(), Some(expected));
+                }
+
+                #[test]
+                fn with_comparator() {
+                    let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+                    let tx_2 = Transaction::new(vec![0, 1, 2, 3, 4], 2);
+                    let tx_3 = Transaction::new(vec![0, 1, 2, 3, 4], 3);
+
+                    let reverse_nonce =
+                        |a: &Transaction, b: &Transaction| b.nonce().cmp(&a.nonce());
+                    let mut mempool = empty_mempool().with_comparator(Box::new(reverse_nonce));
+                    mempool.insert(tx_1.id.clone(), tx_1.clone(), 1);
+                    mempool.insert(tx_2.id.clone(), tx_2.clone(), 1);
+                    mempool.insert(tx_3.id.clone(), tx_3.clone(), 1);
+
+                    let transactions = mempool.get_all_transactions();
+                    assert_eq!(transactions, Some(vec![tx_3, tx_2, tx_1]));
+                }
+            }
+        };
     }
+
+    mempool_tests!(btree_map_store, BTreeMap<Keccak256, Entry>);
+    mempool_tests!(vec_store, VecStore);
 }
@@ -0,0 +1,100 @@
+use super::utils;
+use super::utils::{Domain, Keccak256};
+
+/// Computes the Merkle root of the given leaves. Unpaired nodes are promoted to
+/// the next level unchanged.
+pub fn merkle_root(leaves: &[Keccak256]) -> Option<Keccak256> {
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|nodes| match nodes {
+                [left, right] => hash_pair(left, right),
+                [node] => node.clone(),
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    level.pop()
+}
+
+/// Hashes two sibling nodes into their parent node.
+fn hash_pair(left: &Keccak256, right: &Keccak256) -> Keccak256 {
+    utils::hash(Domain::Merkle, [left.as_bytes(), right.as_bytes()].concat())
+}
+
+/// A Merkle tree which is built one leaf at a time. Every push and root
+/// computation takes O(log n) hashes.
+#[derive(Debug, Default)]
+pub struct IncrementalMerkle {
+    /// Roots of the complete subtrees which haven't been paired yet. The
+    /// subtree at index `i` contains `2^i` leaves.
+    frontier: Vec<Option<Keccak256>>,
+}
+
+impl IncrementalMerkle {
+    /// Creates a new IncrementalMerkle.
+    pub fn new() -> Self {
+        IncrementalMerkle {
+            frontier: Vec::new(),
+        }
+    }
+
+    /// Appends a new leaf.
+    pub fn push(&mut self, leaf: Keccak256) {
+        let mut node = leaf;
+        let mut level = 0;
+        // Merge complete subtrees of the same size until we find a free slot.
+        while let Some(Some(left)) = self.frontier.get_mut(level).map(Option::take) {
+            node = hash_pair(&left, &node);
+            level += 1;
+        }
+        if level == self.frontier.len() {
+            self.frontier.push(None);
+        }
+        self.frontier[level] = Some(node);
+    }
+
+    /// Returns the current Merkle root.
+    pub fn root(&self) -> Option<Keccak256> {
+        self.frontier
+            .iter()
+            .flatten()
+            .fold(None, |root, node| match root {
+                Some(right) => Some(hash_pair(node, &right)),
+                None => Some(node.clone()),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merkle_root_of_no_leaves() {
+        assert_eq!(merkle_root(&[]), None);
+        assert_eq!(IncrementalMerkle::new().root(), None);
+    }
+
+    #[test]
+    fn merkle_root_of_three_leaves() {
+        let leaves: Vec<Keccak256> = (0..3u8).map(|i| utils::hash(Domain::Merkle, [i])).collect();
+
+        let expected = hash_pair(&hash_pair(&leaves[0], &leaves[1]), &leaves[2]);
+        assert_eq!(merkle_root(&leaves), Some(expected));
+    }
+
+    #[test]
+    fn incremental_matches_from_scratch() {
+        for len in 1..=8u8 {
+            let leaves: Vec<Keccak256> =
+                (0..len).map(|i| utils::hash(Domain::Merkle, [i])).collect();
+
+            let mut tree = IncrementalMerkle::new();
+            leaves.iter().for_each(|leaf| tree.push(leaf.clone()));
+
+            assert_eq!(tree.root(), merkle_root(&leaves));
+        }
+    }
+}
@@ -1,13 +1,112 @@
+use std::{
+    cell::Cell,
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
 use crate::{
-    block::Block,
-    chain::Chain,
+    block::{Block, BlockError, MAX_EXTRA_DATA_BYTES},
+    chain::{Chain, ChainError},
     transaction::Transaction,
     utils::{Keccak256, Sender},
 };
-use crate::{mempool::Mempool, utils::hash};
+use crate::{
+    mempool::Mempool,
+    utils::{hash, Domain},
+};
 
 use rand::prelude::SliceRandom;
 
+/// Maximum number of Transaction indexes cached by a Node.
+const INDEX_CACHE_CAPACITY: usize = 1024;
+
+/// Errors which can occur when a Node applies a [Block](crate::block::Block).
+#[derive(Debug, PartialEq)]
+pub enum NodeError {
+    /// The Blocks id doesn't match its contents.
+    InvalidBlock,
+    /// The Block doesn't reference the current tip of the Chain.
+    InvalidPreviousBlock,
+    /// The Block includes a Transaction whose id doesn't match its contents.
+    InvalidTransaction(Keccak256),
+    /// The Chain is inconsistent.
+    InvalidChain(ChainError),
+    /// The pending Transaction with the given id reuses a finalized nonce.
+    ConflictingTransaction(Keccak256),
+    /// The Block was proposed by an entity which isn't a registered validator.
+    UnknownProposer,
+}
+
+/// Status of a [Transaction](crate::transaction::Transaction) known to a Node.
+#[derive(Debug, PartialEq)]
+pub enum TxStatus {
+    /// The Transaction is waiting in the Mempool.
+    Pending,
+    /// The Transaction is included in the Block at the given height.
+    Finalized {
+        /// Height of the Block which includes the Transaction.
+        height: u64,
+    },
+    /// The Transaction is neither pending nor finalized.
+    Unknown,
+}
+
+/// How a Node derives the Mempool index of a [Transaction](crate::transaction::Transaction).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IndexMode {
+    /// The Transaction id is used as the index. It stays the same when the tip
+    /// of the Chain changes.
+    TransactionId,
+    /// The index mixes the Transaction id with the tip of the Chain, so pending
+    /// Transactions are re-indexed whenever a Block is finalized.
+    TipBound,
+}
+
+/// Outcome of adding a [Transaction](crate::transaction::Transaction) to a Node.
+#[derive(Debug, PartialEq)]
+pub struct AddOutcome {
+    /// Whether the Transaction was stored in the Mempool.
+    pub inserted: bool,
+    /// The pending Transaction which was displaced by the added Transaction.
+    pub replaced: Option<Transaction>,
+}
+
+/// A proposed [Block](crate::block::Block) alongside the Transactions it left out.
+#[derive(Debug, PartialEq)]
+pub struct BlockProposal {
+    /// The proposed Block.
+    pub block: Block,
+    /// Ids of the pending Transactions included in the Block.
+    pub included_tx_ids: Vec<Keccak256>,
+    /// Ids of the pending Transactions which didn't fit into the Block.
+    pub excluded_tx_ids: Vec<Keccak256>,
+}
+
+impl BlockProposal {
+    /// Returns the share of the pending Transactions which were included in the Block.
+    pub fn mempool_coverage(&self) -> f64 {
+        let included = self.included_tx_ids.len();
+        let total = included + self.excluded_tx_ids.len();
+        if total == 0 {
+            return 1.0;
+        }
+        included as f64 / total as f64
+    }
+}
+
+/// Timings of the latest Block proposed by a Node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProposalMetrics {
+    /// Time it took to assemble the Block.
+    pub duration: Duration,
+    /// Time it took to select the pending Transactions included in the Block.
+    pub selection_duration: Duration,
+    /// Number of Transactions included in the Block.
+    pub included_transactions: usize,
+    /// Number of pending Transactions which didn't fit into the Block.
+    pub excluded_transactions: usize,
+}
+
 /// A Node that continuously proposes and finalizes [Blocks](crate::block::Block).
 pub struct Node {
     /// Blockchain.
@@ -16,19 +115,85 @@ pub struct Node {
     mempool: Mempool,
     /// Nonce used in Transactions to mitigate replay attacks.
     nonce: u64,
+    /// Maximum number of Transactions included in a proposed Block.
+    max_block_transactions: Option<usize>,
+    /// Maximum number of Transactions of a single sender included in a proposed Block.
+    max_txs_per_sender_per_block: Option<usize>,
+    /// How Mempool indexes are derived from Transactions.
+    index_mode: IndexMode,
+    /// Cache of the Transaction indexes generated for the current tip.
+    index_cache: IndexCache,
+    /// Public key which identifies the Node as the proposer of its Blocks.
+    public_key: Vec<u8>,
+    /// Stake of every registered validator by its public key.
+    validators: HashMap<Vec<u8>, u64>,
+    /// Timings of the latest proposed Block.
+    last_proposal_metrics: Cell<Option<ProposalMetrics>>,
 }
 
 impl Node {
-    /// Creates a new Node.
+    /// Creates a new Node with the default configuration.
     pub fn new() -> Self {
-        let chain = Chain::new(1000);
-        let mempool = Mempool::new();
+        NodeBuilder::new().build()
+    }
 
-        Node {
-            chain,
-            mempool,
-            nonce: 1,
+    /// Returns a reference to the Chain.
+    pub fn chain(&self) -> &Chain {
+        &self.chain
+    }
+
+    /// Returns the public key which identifies the Node as the proposer of its Blocks.
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    /// Registers a validator with the given stake. The stake of a validator
+    /// which is already registered is updated.
+    pub fn register_validator(&mut self, public_key: Vec<u8>, stake: u64) {
+        self.validators.insert(public_key, stake);
+    }
+
+    /// Returns whether the given public key belongs to a registered validator.
+    pub fn is_validator(&self, public_key: &[u8]) -> bool {
+        self.validators.contains_key(public_key)
+    }
+
+    /// Returns the stake of all registered validators. The total saturates at
+    /// `u64::MAX`.
+    pub fn total_stake(&self) -> u64 {
+        self.validators
+            .values()
+            .fold(0, |total, stake| total.saturating_add(*stake))
+    }
+
+    /// Selects the leader of the given slot among the registered validators. The
+    /// probability of a validator being picked is proportional to its stake and
+    /// the selection only depends on the slot, the tip of the Chain and the
+    /// validators, so all Nodes which share them agree on the leader.
+    pub fn select_leader(&self, slot: u64) -> Option<Vec<u8>> {
+        let total_stake = self.total_stake();
+        if total_stake == 0 {
+            return None;
         }
+
+        let seed = hash(
+            Domain::Leader,
+            bincode::serialize(&(slot, self.chain.tip_id())).unwrap(),
+        );
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&seed.as_bytes()[..8]);
+        let mut target = u64::from_le_bytes(bytes) % total_stake;
+
+        // Validators are sorted so that every Node walks them in the same order.
+        let mut validators: Vec<(&Vec<u8>, &u64)> = self.validators.iter().collect();
+        validators.sort();
+        for (public_key, stake) in validators.into_iter() {
+            if target < *stake {
+                return Some(public_key.clone());
+            }
+            target -= stake;
+        }
+        None
     }
 
     /// Create a new Transaction initiated by the Node.
@@ -37,7 +202,7 @@ impl Node {
         let mut rng = rand::thread_rng();
         let mut numbers: Vec<u8> = (1..100).collect();
         numbers.shuffle(&mut rng);
-        let sender: Sender = hash(numbers);
+        let sender = Transaction::derive_sender(&numbers);
 
         // Create a new Transaction.
         let tx = Transaction::new(sender, self.nonce);
@@ -49,33 +214,105 @@ impl Node {
         self.nonce += 1;
     }
 
-    /// Add a single Transaction into the Mempool.
-    pub fn add_transaction(&mut self, transaction: Transaction) {
+    /// Add a single Transaction into the Mempool and report whether it displaced
+    /// a pending Transaction.
+    pub fn add_transaction(&mut self, transaction: Transaction) -> AddOutcome {
         let index = self.generate_transaction_index(&transaction);
-        self.mempool.insert(index, transaction);
+        let height = self.next_height();
+        let tx_id = transaction.id.clone();
+        let replaced = self.mempool.insert(index.clone(), transaction, height);
+        let inserted = self.mempool.get(&index).map(|tx| &tx.id) == Some(&tx_id);
+        AddOutcome { inserted, replaced }
     }
 
     /// Add multiple Transactions into the Mempool.
     pub fn add_transactions(&mut self, transactions: Vec<Transaction>) {
-        transactions
-            .into_iter()
-            .for_each(|tx| self.add_transaction(tx));
+        for tx in transactions.into_iter() {
+            self.add_transaction(tx);
+        }
     }
 
     /// Propose a new Block based on the Transactions in the Mempool.
     pub fn propose_block(&self) -> Option<Block> {
-        if let Some(transactions) = self.mempool.get_all_transactions() {
-            let mut prev_block_id = None;
-            if let Some(block) = self.chain.last() {
-                prev_block_id = Some(block.id.clone());
+        self.preview_block().map(|proposal| proposal.block)
+    }
+
+    /// Propose a Block without Transactions which extends the tip of the Chain.
+    /// It can be used as a heartbeat while no Transactions are pending.
+    pub fn propose_empty_block(&self) -> Block {
+        let prev_block_id = self.chain.tip_id().cloned();
+        Block::with_extra_data(Vec::new(), prev_block_id, self.public_key.clone())
+    }
+
+    /// Propose a new Block and report which pending Transactions were included.
+    pub fn preview_block(&self) -> Option<BlockProposal> {
+        let start = Instant::now();
+        let transactions = self.mempool.get_all_transactions()?;
+        let (mut transactions, mut excluded) = match self.max_txs_per_sender_per_block {
+            Some(max) => Node::cap_per_sender(transactions, max),
+            None => (transactions, Vec::new()),
+        };
+        if let Some(max) = self.max_block_transactions {
+            if max < transactions.len() {
+                excluded.extend(transactions.split_off(max));
             }
-            return Some(Block::new(transactions, prev_block_id));
         }
-        None
+
+        let selection_duration = start.elapsed();
+
+        let included_tx_ids: Vec<Keccak256> = transactions.iter().map(|tx| tx.id.clone()).collect();
+        let excluded_tx_ids: Vec<Keccak256> = excluded.into_iter().map(|tx| tx.id).collect();
+        let prev_block_id = self.chain.tip_id().cloned();
+        let block = Block::with_extra_data(transactions, prev_block_id, self.public_key.clone());
+
+        self.last_proposal_metrics.set(Some(ProposalMetrics {
+            duration: start.elapsed(),
+            selection_duration,
+            included_transactions: included_tx_ids.len(),
+            excluded_transactions: excluded_tx_ids.len(),
+        }));
+        Some(BlockProposal {
+            block,
+            included_tx_ids,
+            excluded_tx_ids,
+        })
+    }
+
+    /// Returns the timings of the latest Block proposed by the Node.
+    pub fn last_proposal_metrics(&self) -> Option<ProposalMetrics> {
+        self.last_proposal_metrics.get()
+    }
+
+    /// Splits the given Transactions into the ones which respect the maximum number
+    /// of Transactions per sender and the ones which don't. The Transactions with
+    /// the lowest nonces of every sender are kept.
+    fn cap_per_sender(
+        transactions: Vec<Transaction>,
+        max: usize,
+    ) -> (Vec<Transaction>, Vec<Transaction>) {
+        let mut by_nonce: Vec<&Transaction> = transactions.iter().collect();
+        by_nonce.sort_by_key(|tx| tx.nonce());
+
+        let mut counts: HashMap<&Sender, usize> = HashMap::new();
+        let capped: HashSet<Keccak256> = by_nonce
+            .into_iter()
+            .filter(|tx| {
+                let count = counts.entry(tx.sender()).or_insert(0);
+                *count += 1;
+                *count > max
+            })
+            .map(|tx| tx.id.clone())
+            .collect();
+
+        transactions
+            .into_iter()
+            .partition(|tx| !capped.contains(&tx.id))
     }
 
     /// Finalize a Block by appending it to the Chain and removing the Transactions from the Mempool.
-    pub fn finalize_block(&mut self, block: Block) {
+    /// The Block isn't validated, so Blocks received from other Nodes go through
+    /// [apply_block](Node::apply_block).
+    pub(crate) fn finalize_block(&mut self, block: Block) {
         // Get Transaction indexes of Transactions included in the Block.
         let tx_indexes: Vec<Keccak256> = block
             .transactions
@@ -83,30 +320,307 @@ impl Node {
             .map(|tx| self.generate_transaction_index(tx))
             .collect();
 
+        // Get the latest nonce of every sender with a Transaction in the Block.
+        let mut latest_nonces: HashMap<Sender, u64> = HashMap::new();
+        for tx in block.transactions.iter() {
+            let nonce = latest_nonces.entry(tx.sender().clone()).or_insert(0);
+            *nonce = (*nonce).max(tx.nonce());
+        }
+
         // Append the Block to the Chain.
         self.chain.append(block);
+        self.index_cache.clear();
 
         // Remove all Transactions included in the Block from the Mempool.
         self.mempool.remove_transactions(tx_indexes);
 
-        // Repopulate Mempool (if necessary).
-        if let Some(transactions) = self.mempool.get_all_transactions() {
-            self.mempool.clear();
-            transactions.into_iter().for_each(|tx| {
+        // Remove Transactions which can't become valid anymore.
+        self.mempool.drop_stale_nonces(&latest_nonces);
+
+        // Repopulate Mempool (if necessary). Only tip bound indexes change with the
+        // tip. The drained Transactions are moved back into the Mempool rather
+        // than cloned.
+        if self.index_mode == IndexMode::TipBound {
+            for (tx, inserted_at_height) in self.mempool.drain() {
                 let index = self.generate_transaction_index(&tx);
-                self.mempool.insert(index, tx);
-            });
+                self.mempool.insert(index, tx, inserted_at_height);
+            }
+        }
+    }
+
+    /// Returns whether the Transaction with the given id is pending or finalized.
+    pub fn transaction_status(&self, tx_id: &Keccak256) -> TxStatus {
+        if self.mempool.find(tx_id).is_some() {
+            return TxStatus::Pending;
+        }
+        match self.chain.find_transaction(tx_id) {
+            Some((height, _)) => TxStatus::Finalized { height },
+            None => TxStatus::Unknown,
+        }
+    }
+
+    /// Returns the number of Blocks which confirm the Transaction with the given id,
+    /// counting the Block which includes it. Pending and unknown Transactions have
+    /// no confirmations.
+    pub fn confirmations(&self, tx_id: &Keccak256) -> Option<u64> {
+        let (inclusion_height, _) = self.chain.find_transaction(tx_id)?;
+        let tip_height = self.chain.height()?;
+        Some(tip_height - inclusion_height + 1)
+    }
+
+    /// Returns the height of the next Block appended to the Chain.
+    fn next_height(&self) -> u64 {
+        self.chain.height().map_or(0, |height| height + 1)
+    }
+
+    /// Validate a Block received from another Node and finalize it. The extra data
+    /// of the Block holds the public key of its proposer, which has to be a
+    /// registered validator.
+    pub fn apply_block(&mut self, block: Block) -> Result<(), NodeError> {
+        if !block.is_valid() {
+            return Err(NodeError::InvalidBlock);
+        }
+
+        // The id covers the extra data, so the proposer can't be swapped out.
+        if !self.is_validator(block.extra_data()) {
+            return Err(NodeError::UnknownProposer);
+        }
+
+        // Ensure that the Block extends the current tip of the Chain.
+        if block.get_previous_block_id() != self.chain.tip_id() {
+            return Err(NodeError::InvalidPreviousBlock);
+        }
+
+        if let Some(tx) = block.transactions.iter().find(|tx| !tx.validate()) {
+            return Err(NodeError::InvalidTransaction(tx.id.clone()));
+        }
+
+        self.finalize_block(block);
+        Ok(())
+    }
+
+    /// Ensure that the Chain is consistent and that no pending Transaction
+    /// reuses the nonce of a finalized Transaction.
+    pub fn verify_state(&self) -> Result<(), NodeError> {
+        self.chain.validate().map_err(NodeError::InvalidChain)?;
+
+        let finalized: HashSet<(Sender, u64)> = self
+            .chain
+            .iter()
+            .flat_map(|block| block.transactions.iter())
+            .map(|tx| (tx.sender().clone(), tx.nonce()))
+            .collect();
+
+        if let Some(tx) = self
+            .mempool
+            .transactions()
+            .find(|tx| finalized.contains(&(tx.sender().clone(), tx.nonce())))
+        {
+            return Err(NodeError::ConflictingTransaction(tx.id.clone()));
+        }
+        Ok(())
+    }
+
+    /// Validates the given Transactions and ensures that none of them reuses a
+    /// nonce which was already finalized for its sender. Returns the result of
+    /// every Transaction in the given order.
+    pub fn validate_transactions(
+        &self,
+        transactions: &[Transaction],
+    ) -> Vec<Result<(), NodeError>> {
+        let mut latest_nonces: HashMap<&Sender, u64> = HashMap::new();
+        for tx in self
+            .chain
+            .iter()
+            .flat_map(|block| block.transactions.iter())
+        {
+            let nonce = latest_nonces.entry(tx.sender()).or_insert(0);
+            *nonce = (*nonce).max(tx.nonce());
         }
+
+        transactions
+            .iter()
+            .map(|tx| {
+                if !tx.validate() {
+                    return Err(NodeError::InvalidTransaction(tx.id.clone()));
+                }
+                match latest_nonces.get(tx.sender()) {
+                    Some(nonce) if tx.nonce() <= *nonce => {
+                        Err(NodeError::ConflictingTransaction(tx.id.clone()))
+                    }
+                    _ => Ok(()),
+                }
+            })
+            .collect()
     }
 
     /// Creates the index used as a Mempool key.
-    fn generate_transaction_index(&self, transaction: &Transaction) -> Keccak256 {
-        let mut block_id = None;
-        if let Some(block) = self.chain.last() {
-            block_id = Some(block.id.clone());
+    fn generate_transaction_index(&mut self, transaction: &Transaction) -> Keccak256 {
+        if self.index_mode == IndexMode::TransactionId {
+            return transaction.id.clone();
+        }
+        let block_id = self.chain.tip_id();
+        if let Some(index) = self.index_cache.get(&transaction.id, block_id) {
+            return index;
+        }
+        let index = Node::compute_transaction_index(transaction, block_id);
+        self.index_cache
+            .insert(transaction.id.clone(), index.clone());
+        index
+    }
+
+    /// Computes the index of a Transaction for the given tip of the Chain.
+    fn compute_transaction_index(
+        transaction: &Transaction,
+        block_id: Option<&Keccak256>,
+    ) -> Keccak256 {
+        let data = bincode::serialize(&(&transaction.id, block_id)).unwrap();
+        hash(Domain::Index, data)
+    }
+}
+
+/// A least recently used cache of Transaction indexes which are only valid for a
+/// single tip of the Chain.
+#[derive(Default)]
+struct IndexCache {
+    /// Tip of the Chain the cached indexes were generated for.
+    tip_id: Option<Keccak256>,
+    /// Cached indexes by Transaction id alongside the tick they were last used at.
+    indexes: HashMap<Keccak256, (Keccak256, u64)>,
+    /// Counter which orders the accesses to the cache.
+    tick: u64,
+}
+
+impl IndexCache {
+    /// Returns the cached index of the Transaction with the given id. The cache is
+    /// cleared if the tip of the Chain changed since the indexes were generated.
+    fn get(&mut self, tx_id: &Keccak256, tip_id: Option<&Keccak256>) -> Option<Keccak256> {
+        if self.tip_id.as_ref() != tip_id {
+            self.clear();
+            self.tip_id = tip_id.cloned();
+            return None;
+        }
+
+        self.tick += 1;
+        let tick = self.tick;
+        self.indexes.get_mut(tx_id).map(|(index, last_used)| {
+            *last_used = tick;
+            index.clone()
+        })
+    }
+
+    /// Caches the index of the Transaction with the given id, evicting the least
+    /// recently used index if the cache is full.
+    fn insert(&mut self, tx_id: Keccak256, index: Keccak256) {
+        if self.indexes.len() >= INDEX_CACHE_CAPACITY && !self.indexes.contains_key(&tx_id) {
+            let least_recent = self
+                .indexes
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(tx_id, _)| tx_id.clone());
+            if let Some(least_recent) = least_recent {
+                self.indexes.remove(&least_recent);
+            }
+        }
+
+        self.tick += 1;
+        self.indexes.insert(tx_id, (index, self.tick));
+    }
+
+    /// Remove all cached indexes.
+    fn clear(&mut self) {
+        self.indexes.clear();
+    }
+}
+
+/// A builder used to configure a [Node](crate::node::Node).
+pub struct NodeBuilder {
+    /// Initial capacity of the Chain.
+    chain_capacity: usize,
+    /// Nonce used for the first Transaction created by the Node.
+    nonce: u64,
+    /// Maximum number of Transactions included in a proposed Block.
+    max_block_transactions: Option<usize>,
+    /// Maximum number of Transactions of a single sender included in a proposed Block.
+    max_txs_per_sender_per_block: Option<usize>,
+    /// How Mempool indexes are derived from Transactions.
+    index_mode: IndexMode,
+    /// Public key which identifies the Node as the proposer of its Blocks.
+    public_key: Vec<u8>,
+}
+
+impl NodeBuilder {
+    /// Creates a new NodeBuilder with the default configuration.
+    pub fn new() -> Self {
+        NodeBuilder {
+            chain_capacity: 1000,
+            nonce: 1,
+            max_block_transactions: None,
+            max_txs_per_sender_per_block: None,
+            index_mode: IndexMode::TransactionId,
+            public_key: Vec::new(),
+        }
+    }
+
+    /// Sets the initial capacity of the Chain.
+    pub fn chain_capacity(mut self, chain_capacity: usize) -> Self {
+        self.chain_capacity = chain_capacity;
+        self
+    }
+
+    /// Sets the nonce used for the first Transaction created by the Node.
+    pub fn nonce(mut self, nonce: u64) -> Self {
+        self.nonce = nonce;
+        self
+    }
+
+    /// Sets the maximum number of Transactions included in a proposed Block.
+    pub fn max_block_transactions(mut self, max_block_transactions: usize) -> Self {
+        self.max_block_transactions = Some(max_block_transactions);
+        self
+    }
+
+    /// Sets the maximum number of Transactions of a single sender included in a
+    /// proposed Block. The remaining Transactions stay in the Mempool.
+    pub fn max_txs_per_sender_per_block(mut self, max_txs_per_sender_per_block: usize) -> Self {
+        self.max_txs_per_sender_per_block = Some(max_txs_per_sender_per_block);
+        self
+    }
+
+    /// Sets how Mempool indexes are derived from Transactions.
+    pub fn index_mode(mut self, index_mode: IndexMode) -> Self {
+        self.index_mode = index_mode;
+        self
+    }
+
+    /// Sets the public key which identifies the Node as the proposer of its Blocks.
+    /// It's stored as the extra data of proposed Blocks, so keys which are longer
+    /// than [MAX_EXTRA_DATA_BYTES] are rejected.
+    pub fn public_key(mut self, public_key: Vec<u8>) -> Result<Self, BlockError> {
+        if public_key.len() > MAX_EXTRA_DATA_BYTES {
+            return Err(BlockError::ExtraDataTooLarge);
+        }
+        self.public_key = public_key;
+        Ok(self)
+    }
+
+    /// Creates the configured Node.
+    pub fn build(self) -> Node {
+        let chain = Chain::new(self.chain_capacity);
+        let mempool = Mempool::new();
+
+        Node {
+            chain,
+            mempool,
+            nonce: self.nonce,
+            max_block_transactions: self.max_block_transactions,
+            max_txs_per_sender_per_block: self.max_txs_per_sender_per_block,
+            index_mode: self.index_mode,
+            index_cache: IndexCache::default(),
+            public_key: self.public_key,
+            validators: HashMap::new(),
+            last_proposal_metrics: Cell::new(None),
         }
-        let data = bincode::serialize(&(transaction.id.clone(), block_id)).unwrap();
-        hash(data)
     }
 }
 
@@ -114,6 +628,19 @@ impl Node {
 mod tests {
     use super::*;
 
+    /// Public key of the validator which proposes the Blocks applied in the tests.
+    const PROPOSER: [u8; 3] = [1, 2, 3];
+
+    /// Creates a Node which proposes Blocks as [PROPOSER] and accepts them.
+    fn validator_node() -> Node {
+        let mut node = NodeBuilder::new()
+            .public_key(PROPOSER.to_vec())
+            .unwrap()
+            .build();
+        node.register_validator(PROPOSER.to_vec(), 10);
+        node
+    }
+
     #[test]
     fn new_node() {
         let node = Node::new();
@@ -121,6 +648,53 @@ mod tests {
         assert_eq!(node.mempool.get_all_transactions(), None);
         assert_eq!(node.chain.height(), None);
         assert_eq!(node.nonce, 1);
+        assert_eq!(node.max_block_transactions, None);
+    }
+
+    #[test]
+    fn node_builder() {
+        let mut node = NodeBuilder::new()
+            .chain_capacity(10)
+            .nonce(42)
+            .max_block_transactions(2)
+            .build();
+        assert_eq!(node.nonce, 42);
+        assert_eq!(node.max_block_transactions, Some(2));
+        assert_eq!(node.index_mode, IndexMode::TransactionId);
+
+        node.create_transaction();
+        node.create_transaction();
+        node.create_transaction();
+        assert_eq!(node.nonce, 45);
+
+        // Only 2 of the 3 pending Transactions should be included in the Block.
+        let block = node.propose_block().unwrap();
+        assert_eq!(block.transactions.len(), 2);
+
+        node.finalize_block(block);
+        assert_eq!(node.mempool.len(), 1);
+    }
+
+    #[test]
+    fn node_builder_public_key() {
+        // The public key has to fit into the extra data of proposed Blocks.
+        let result = NodeBuilder::new().public_key(vec![1; MAX_EXTRA_DATA_BYTES + 1]);
+        assert!(matches!(result, Err(BlockError::ExtraDataTooLarge)));
+
+        let node = NodeBuilder::new()
+            .public_key(vec![1; MAX_EXTRA_DATA_BYTES])
+            .unwrap()
+            .build();
+        assert_eq!(node.public_key().len(), MAX_EXTRA_DATA_BYTES);
+        let block = node.propose_empty_block();
+        assert_eq!(block.extra_data(), node.public_key());
+        let encoded = Block::serialize(
+            block.version(),
+            &block.transactions,
+            block.get_previous_block_id(),
+            block.extra_data(),
+        );
+        assert_eq!(Block::deserialize(encoded), Ok(block));
     }
 
     #[test]
@@ -138,9 +712,27 @@ mod tests {
         let mut node = Node::new();
         let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
 
-        node.add_transaction(tx);
+        let outcome = node.add_transaction(tx.clone());
+        assert_eq!(
+            outcome,
+            AddOutcome {
+                inserted: true,
+                replaced: None
+            }
+        );
         assert_eq!(node.mempool.len(), 1);
         assert_eq!(node.nonce, 1);
+
+        // Re-submitting the Transaction overwrites the pending one.
+        let outcome = node.add_transaction(tx.clone());
+        assert_eq!(
+            outcome,
+            AddOutcome {
+                inserted: true,
+                replaced: Some(tx)
+            }
+        );
+        assert_eq!(node.mempool.len(), 1);
     }
 
     #[test]
@@ -173,6 +765,118 @@ mod tests {
         assert_eq!(block.get_previous_block_id(), None);
     }
 
+    #[test]
+    fn preview_block() {
+        let mut node = NodeBuilder::new().max_block_transactions(2).build();
+
+        // Preview a Block when 0 Transactions are in the Mempool.
+        assert_eq!(node.preview_block(), None);
+
+        node.create_transaction();
+        node.create_transaction();
+        node.create_transaction();
+
+        let proposal = node.preview_block().unwrap();
+        let pending: Vec<Keccak256> = node
+            .mempool
+            .get_all_transactions()
+            .unwrap()
+            .into_iter()
+            .map(|tx| tx.id)
+            .collect();
+        assert_eq!(proposal.included_tx_ids, pending[..2].to_vec());
+        assert_eq!(proposal.excluded_tx_ids, pending[2..].to_vec());
+        assert_eq!(proposal.block.transactions.len(), 2);
+        assert_eq!(node.propose_block(), Some(proposal.block));
+
+        // Previewing a Block doesn't touch the Mempool.
+        assert_eq!(node.mempool.len(), 3);
+    }
+
+    #[test]
+    fn mempool_coverage() {
+        let mut node = NodeBuilder::new().max_block_transactions(4).build();
+        for _ in 0..10 {
+            node.create_transaction();
+        }
+
+        let proposal = node.preview_block().unwrap();
+        assert!((proposal.mempool_coverage() - 0.4).abs() < f64::EPSILON);
+
+        let node = NodeBuilder::new().build();
+        let proposal = BlockProposal {
+            block: node.propose_empty_block(),
+            included_tx_ids: Vec::new(),
+            excluded_tx_ids: Vec::new(),
+        };
+        assert_eq!(proposal.mempool_coverage(), 1.0);
+    }
+
+    #[test]
+    fn preview_block_per_sender_cap() {
+        let mut node = NodeBuilder::new().max_txs_per_sender_per_block(2).build();
+        let busy: Vec<Transaction> = (1..=5)
+            .map(|nonce| Transaction::new(vec![0, 1, 2, 3, 4], nonce))
+            .collect();
+        let other = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+        node.add_transactions(busy.clone());
+        node.add_transaction(other.clone());
+
+        // Only the 2 Transactions with the lowest nonces of the busy sender are included.
+        let proposal = node.preview_block().unwrap();
+        let included: Vec<&Transaction> = proposal.block.transactions.iter().collect();
+        assert_eq!(included.len(), 3);
+        assert!(included.contains(&&busy[0]));
+        assert!(included.contains(&&busy[1]));
+        assert!(included.contains(&&other));
+        assert_eq!(proposal.excluded_tx_ids.len(), 3);
+
+        // The remaining Transactions of the busy sender stay in the Mempool.
+        node.finalize_block(proposal.block);
+        assert_eq!(node.mempool.len(), 3);
+        for tx in busy[2..].iter() {
+            assert!(node.mempool.find(&tx.id).is_some());
+        }
+    }
+
+    #[test]
+    fn last_proposal_metrics() {
+        let mut node = NodeBuilder::new().max_block_transactions(2).build();
+        assert_eq!(node.last_proposal_metrics(), None);
+
+        node.create_transaction();
+        node.create_transaction();
+        node.create_transaction();
+        let block = node.propose_block().unwrap();
+
+        let metrics = node.last_proposal_metrics().unwrap();
+        assert_eq!(metrics.included_transactions, block.transactions.len());
+        assert_eq!(metrics.excluded_transactions, 1);
+        assert!(metrics.selection_duration <= metrics.duration);
+    }
+
+    #[test]
+    fn finalize_empty_block() {
+        let mut node = validator_node();
+        node.add_transaction(Transaction::new(vec![0, 1, 2, 3, 4], 1));
+        let block = node.propose_block().unwrap();
+        node.finalize_block(block);
+
+        node.add_transaction(Transaction::new(vec![5, 6, 7, 8, 9], 1));
+        let pending = node.mempool.get_all_transactions();
+
+        let empty_block = node.propose_empty_block();
+        assert!(empty_block.transactions.is_empty());
+        assert_eq!(empty_block.get_previous_block_id(), node.chain.tip_id());
+        assert_eq!(empty_block.extra_data(), &PROPOSER[..]);
+
+        node.apply_block(empty_block.clone()).unwrap();
+        assert_eq!(node.chain.height(), Some(1));
+        assert_eq!(node.chain.last(), Some(&empty_block));
+        // The Mempool keeps its pending Transactions.
+        assert_eq!(node.mempool.get_all_transactions(), pending);
+    }
+
     #[test]
     fn finalize_single_block() {
         let mut node = Node::new();
@@ -231,6 +935,63 @@ mod tests {
         assert_eq!(node.mempool.len(), 2);
     }
 
+    #[test]
+    fn finalize_block_drops_stale_nonces() {
+        let mut node = Node::new();
+        let stale = Transaction::new(vec![0, 1, 2, 3, 4], 2);
+        let other = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+        node.add_transactions(vec![stale.clone(), other.clone()]);
+
+        let tx = Transaction::new(vec![0, 1, 2, 3, 4], 3);
+        node.finalize_block(Block::new(vec![tx], None));
+
+        assert_eq!(node.mempool.find(&stale.id), None);
+        assert_eq!(node.mempool.get_all_transactions(), Some(vec![other]));
+    }
+
+    #[test]
+    fn finalize_block_large_batch() {
+        let mut node = Node::new();
+        let transactions: Vec<Transaction> = (0..2000u64)
+            .map(|nonce| Transaction::new(nonce.to_le_bytes().to_vec(), nonce))
+            .collect();
+        node.add_transactions(transactions.clone());
+        assert_eq!(node.mempool.len(), 2000);
+
+        let (included, pending) = transactions.split_at(500);
+        node.finalize_block(Block::new(included.to_vec(), None));
+
+        // Every pending Transaction is still stored under its index.
+        assert_eq!(node.mempool.len(), pending.len());
+        for tx in pending {
+            let index = node.generate_transaction_index(tx);
+            assert_eq!(node.mempool.get(&index), Some(tx));
+        }
+        for tx in included {
+            assert_eq!(node.mempool.find(&tx.id), None);
+        }
+        assert_eq!(node.verify_state(), Ok(()));
+    }
+
+    #[test]
+    fn finalize_block_keeps_insertion_height() {
+        let mut node = NodeBuilder::new().index_mode(IndexMode::TipBound).build();
+        let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        let tx_2 = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+
+        node.add_transaction(tx_1.clone());
+        let block = Block::new(vec![tx_1], None);
+
+        // Insert a Transaction which isn't included in the Block.
+        node.add_transaction(tx_2.clone());
+        node.finalize_block(block);
+
+        // The pending Transaction was re-indexed but kept its insertion height.
+        let index = node.generate_transaction_index(&tx_2);
+        assert_eq!(node.next_height(), 1);
+        assert_eq!(node.mempool.age_of(&index, node.next_height()), Some(1));
+    }
+
     #[test]
     fn lifecycle() {
         let mut node = Node::new();
@@ -288,18 +1049,238 @@ mod tests {
     }
 
     #[test]
-    fn generate_transaction_index() {
+    fn apply_block() {
+        let mut node = validator_node();
+        let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        node.add_transaction(tx.clone());
+
+        let block = node.propose_block().unwrap();
+        assert_eq!(block.transactions, vec![tx]);
+        assert_eq!(node.apply_block(block.clone()), Ok(()));
+        assert_eq!(node.chain.last(), Some(&block));
+        assert_eq!(node.mempool.len(), 0);
+    }
+
+    #[test]
+    fn apply_block_invalid_block() {
         let mut node = Node::new();
         let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
 
+        let mut block = Block::new(vec![tx], None);
+        block.id = [0; 32].into();
+        assert_eq!(node.apply_block(block), Err(NodeError::InvalidBlock));
+        assert_eq!(node.chain.height(), None);
+    }
+
+    #[test]
+    fn apply_block_invalid_previous_block() {
+        let mut node = validator_node();
+        let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+
+        let block = Block::with_extra_data(vec![tx], Some([5; 32].into()), PROPOSER.to_vec());
+        assert_eq!(
+            node.apply_block(block),
+            Err(NodeError::InvalidPreviousBlock)
+        );
+        assert_eq!(node.chain.height(), None);
+    }
+
+    #[test]
+    fn apply_block_invalid_transaction() {
+        let mut node = validator_node();
+        let mut tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        tx.id = [5; 32].into();
+
+        let block = Block::with_extra_data(vec![tx], None, PROPOSER.to_vec());
+        assert_eq!(
+            node.apply_block(block),
+            Err(NodeError::InvalidTransaction([5; 32].into()))
+        );
+        assert_eq!(node.chain.height(), None);
+    }
+
+    #[test]
+    fn register_validator() {
+        let mut node = Node::new();
+        assert_eq!(node.total_stake(), 0);
+        assert!(!node.is_validator(&[1, 2, 3]));
+
+        node.register_validator(vec![1, 2, 3], 10);
+        node.register_validator(vec![4, 5, 6], 20);
+        assert!(node.is_validator(&[1, 2, 3]));
+        assert_eq!(node.total_stake(), 30);
+
+        // Registering a validator again updates its stake.
+        node.register_validator(vec![1, 2, 3], 5);
+        assert_eq!(node.total_stake(), 25);
+
+        // The total stake saturates instead of overflowing.
+        node.register_validator(vec![7, 8, 9], u64::MAX);
+        assert_eq!(node.total_stake(), u64::MAX);
+    }
+
+    #[test]
+    fn select_leader() {
+        let mut node = Node::new();
+        assert_eq!(node.select_leader(0), None);
+
+        let stakes = [(vec![1], 10), (vec![2], 30), (vec![3], 60), (vec![4], 0)];
+        let mut other = Node::new();
+        for (public_key, stake) in stakes.iter() {
+            node.register_validator(public_key.clone(), *stake);
+            other.register_validator(public_key.clone(), *stake);
+        }
+
+        // Nodes with the same validators and tip agree on the leader.
+        let mut counts: HashMap<Vec<u8>, u64> = HashMap::new();
+        for slot in 0..2000 {
+            let leader = node.select_leader(slot).unwrap();
+            assert_eq!(other.select_leader(slot), Some(leader.clone()));
+            *counts.entry(leader).or_insert(0) += 1;
+        }
+
+        // The share of slots led by a validator tracks its stake.
+        for (public_key, stake) in stakes.iter() {
+            let share = counts.get(public_key).copied().unwrap_or(0) as f64 / 2000.0;
+            assert!((share - *stake as f64 / 100.0).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn apply_block_unknown_proposer() {
+        let mut node = validator_node();
+        let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+
+        let block = Block::with_extra_data(vec![tx.clone()], None, vec![4, 5, 6]);
+        assert_eq!(node.apply_block(block), Err(NodeError::UnknownProposer));
+        // A Block without a proposer is rejected as well.
+        let block = Block::new(vec![tx.clone()], None);
+        assert_eq!(node.apply_block(block), Err(NodeError::UnknownProposer));
+        assert_eq!(node.chain.height(), None);
+
+        let block = Block::with_extra_data(vec![tx], None, PROPOSER.to_vec());
+        node.apply_block(block).unwrap();
+        assert_eq!(node.chain.height(), Some(0));
+    }
+
+    #[test]
+    fn verify_state() {
+        let mut node = Node::new();
+        let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        let tx_2 = Transaction::new(vec![0, 1, 2, 3, 4], 2);
+
+        node.add_transaction(tx_1.clone());
+        let block = node.propose_block().unwrap();
+        node.finalize_block(block);
+        node.add_transaction(tx_2);
+        assert_eq!(node.verify_state(), Ok(()));
+
+        // Sneak an already finalized Transaction back into the Mempool.
+        node.mempool.insert([1; 32].into(), tx_1.clone(), 0);
+        assert_eq!(
+            node.verify_state(),
+            Err(NodeError::ConflictingTransaction(tx_1.id))
+        );
+    }
+
+    #[test]
+    fn validate_transactions() {
+        let mut node = Node::new();
+        let finalized = Transaction::new(vec![0, 1, 2, 3, 4], 2);
+        node.finalize_block(Block::new(vec![finalized.clone()], None));
+
+        let valid = Transaction::new(vec![0, 1, 2, 3, 4], 3);
+        let other_sender = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+        let stale = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        let mut tampered = Transaction::new(vec![5, 6, 7, 8, 9], 2);
+        tampered.id = [0; 32].into();
+
+        let transactions = vec![
+            valid,
+            finalized.clone(),
+            other_sender,
+            tampered.clone(),
+            stale.clone(),
+        ];
+        assert_eq!(
+            node.validate_transactions(&transactions),
+            vec![
+                Ok(()),
+                Err(NodeError::ConflictingTransaction(finalized.id)),
+                Ok(()),
+                Err(NodeError::InvalidTransaction(tampered.id)),
+                Err(NodeError::ConflictingTransaction(stale.id)),
+            ]
+        );
+        assert!(node.validate_transactions(&[]).is_empty());
+    }
+
+    #[test]
+    fn transaction_status() {
+        let mut node = Node::new();
+        let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        let tx_2 = Transaction::new(vec![0, 1, 2, 3, 4], 2);
+
+        node.add_transaction(tx_1.clone());
+        assert_eq!(node.transaction_status(&tx_1.id), TxStatus::Pending);
+
+        let block = node.propose_block().unwrap();
+        node.finalize_block(block);
+        node.add_transaction(tx_2.clone());
+        let block = node.propose_block().unwrap();
+        node.finalize_block(block);
+        assert_eq!(
+            node.transaction_status(&tx_1.id),
+            TxStatus::Finalized { height: 0 }
+        );
+        assert_eq!(
+            node.transaction_status(&tx_2.id),
+            TxStatus::Finalized { height: 1 }
+        );
+
+        assert_eq!(node.transaction_status(&[0; 32].into()), TxStatus::Unknown);
+    }
+
+    #[test]
+    fn confirmations() {
+        let mut node = Node::new();
+        let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        let block = node.propose_empty_block();
+        node.finalize_block(block);
+
+        node.add_transaction(tx.clone());
+        assert_eq!(node.confirmations(&tx.id), None);
+
+        let block = node.propose_block().unwrap();
+        node.finalize_block(block);
+        assert_eq!(
+            node.transaction_status(&tx.id),
+            TxStatus::Finalized { height: 1 }
+        );
+        assert_eq!(node.confirmations(&tx.id), Some(1));
+
+        for _ in 0..2 {
+            let block = node.propose_empty_block();
+            node.finalize_block(block);
+        }
+        assert_eq!(node.confirmations(&tx.id), Some(3));
+
+        assert_eq!(node.confirmations(&[0; 32].into()), None);
+    }
+
+    #[test]
+    fn generate_transaction_index() {
+        let mut node = NodeBuilder::new().index_mode(IndexMode::TipBound).build();
+        let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+
         // Generate an index without a Block in the Chain.
         let index = node.generate_transaction_index(&tx);
         assert_eq!(
             index,
-            vec![
-                131, 104, 201, 189, 46, 213, 139, 247, 167, 5, 96, 68, 185, 137, 240, 74, 88, 236,
-                236, 163, 205, 63, 31, 84, 42, 72, 102, 49, 96, 111, 237, 138
-            ]
+            Keccak256::from([
+                204, 13, 138, 24, 150, 183, 41, 27, 187, 209, 85, 197, 90, 244, 40, 5, 190, 147,
+                189, 250, 139, 133, 167, 46, 69, 148, 111, 100, 63, 110, 240, 232
+            ])
         );
 
         // Generate an index with a Block in the Chain.
@@ -308,10 +1289,51 @@ mod tests {
         let index = node.generate_transaction_index(&tx);
         assert_eq!(
             index,
-            vec![
-                207, 58, 24, 227, 9, 92, 25, 41, 58, 138, 229, 70, 116, 80, 222, 43, 52, 244, 40,
-                144, 108, 8, 75, 38, 81, 216, 33, 89, 84, 248, 102, 53
-            ]
+            Keccak256::from([
+                165, 239, 180, 35, 155, 53, 22, 119, 198, 152, 141, 56, 192, 143, 31, 216, 58, 1,
+                141, 173, 129, 58, 250, 182, 225, 23, 63, 106, 94, 208, 149, 237
+            ])
         )
     }
+
+    #[test]
+    fn transaction_index_cache() {
+        let mut node = NodeBuilder::new().index_mode(IndexMode::TipBound).build();
+        let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+
+        node.add_transaction(tx.clone());
+        assert_eq!(node.index_cache.indexes.len(), 1);
+
+        // Cached and freshly computed indexes match.
+        let cached = node.generate_transaction_index(&tx);
+        let computed = Node::compute_transaction_index(&tx, node.chain.tip_id());
+        assert_eq!(cached, computed);
+
+        // Finalizing a Block changes the tip and clears the cache.
+        let block = node.propose_block().unwrap();
+        node.finalize_block(block);
+        assert!(node.index_cache.indexes.is_empty());
+
+        let cached = node.generate_transaction_index(&tx);
+        let computed = Node::compute_transaction_index(&tx, node.chain.tip_id());
+        assert_eq!(cached, computed);
+        assert_eq!(node.index_cache.tip_id.as_ref(), node.chain.tip_id());
+    }
+
+    #[test]
+    fn transaction_id_index() {
+        let mut node = Node::new();
+        let tx_1 = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        let tx_2 = Transaction::new(vec![5, 6, 7, 8, 9], 1);
+        assert_eq!(node.generate_transaction_index(&tx_1), tx_1.id);
+
+        node.add_transactions(vec![tx_1.clone(), tx_2.clone()]);
+        let index = node.generate_transaction_index(&tx_2);
+        node.finalize_block(Block::new(vec![tx_1], None));
+
+        // The pending Transaction keeps its index across the finalization.
+        assert_eq!(node.generate_transaction_index(&tx_2), index);
+        assert_eq!(node.mempool.get(&tx_2.id), Some(&tx_2));
+        assert_eq!(node.mempool.len(), 1);
+    }
 }
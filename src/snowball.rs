@@ -1,9 +1,82 @@
-use std::{collections::HashMap, hash::Hash};
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    Rng,
+};
+
+/// Errors which can occur while running the Snowball algorithm.
+#[derive(Debug, PartialEq)]
+pub enum SnowballError {
+    /// More votes were cast than peers were queried.
+    SampleTooLarge,
+    /// Peers can't be queried before a value is preferred.
+    NoPreference,
+    /// The stakes of the peers add up to more than `u64::MAX`.
+    StakeOverflow,
+}
+
+/// A source of votes which asks peers for their preference.
+pub trait Queryable<T> {
+    /// Identifies the peer which cast a vote.
+    type PeerId: Eq + Hash;
+
+    /// Asks `k` peers for their preference given the queriers preference `item`.
+    /// Every element of the result is the vote of a single peer tagged with its id.
+    fn query(&self, item: &T, k: u8) -> Vec<(Self::PeerId, T)>;
+}
+
+/// Strategy used by [Snowball](crate::snowball::Snowball) to pick the favorite
+/// of a round of votes.
+pub trait VoteCounter<T> {
+    /// Returns the favorite item alongside its votes or `None` if there's no favorite.
+    fn tally(&self, votes: HashMap<T, u64>) -> Option<(T, u64)>;
+}
+
+/// Counts votes for any number of items and favors the item with the most votes.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MajorityCounter;
+
+impl<T> VoteCounter<T> for MajorityCounter {
+    fn tally(&self, votes: HashMap<T, u64>) -> Option<(T, u64)> {
+        let mut favorite: Option<T> = None;
+        let mut favorite_votes = 0;
+        for (item, votes) in votes.into_iter() {
+            if votes > favorite_votes {
+                favorite = Some(item);
+                favorite_votes = votes;
+            }
+        }
+        favorite.map(|favorite| (favorite, favorite_votes))
+    }
+}
+
+/// Counts votes for a binary decision and favors the side with more votes.
+/// A tie doesn't produce a favorite.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BinaryCounter;
+
+impl VoteCounter<bool> for BinaryCounter {
+    fn tally(&self, votes: HashMap<bool, u64>) -> Option<(bool, u64)> {
+        let yes = votes.get(&true).copied().unwrap_or(0);
+        let no = votes.get(&false).copied().unwrap_or(0);
+        if yes > no {
+            Some((true, yes))
+        } else if no > yes {
+            Some((false, no))
+        } else {
+            None
+        }
+    }
+}
 
 /// Himitsu variant of the Snowball algorithm from the family of
 /// [Metastable Consensus Protocols](https://arxiv.org/abs/1906.08936).
 #[derive(Debug, PartialEq)]
-pub struct Snowball<T>
+pub struct Snowball<T, C = MajorityCounter>
 where
     T: Eq + Hash,
 {
@@ -11,6 +84,12 @@ where
     value: Option<T>,
     /// Returns whether the algorithm converged.
     done: bool,
+    /// Returns whether the algorithm gave up before converging.
+    timed_out: bool,
+    /// Records the number of rounds run so far.
+    rounds: u64,
+    /// Maximum number of rounds to run before giving up.
+    max_rounds: Option<u64>,
     /// Records the number of consecutive successes.
     counter: u8,
     /// Records the number of consecutive successes for each individual item.
@@ -23,57 +102,239 @@ where
     quorum_size: u8,
     /// Number of consecutive votes required to consider a decision to be *stable*.
     /// Referred to as `beta` in the whitepaper.
+    ///
+    /// Counters saturate at `u8::MAX`, so a threshold of `u8::MAX` is never reached.
     decision_threshold: u8,
+    /// Strategy used to pick the favorite of a round of votes.
+    vote_counter: C,
+    /// Whether every peer only contributes a single vote to rounds run via
+    /// [tick_from_peers](Snowball::tick_from_peers) and [poll](Snowball::poll).
+    require_distinct_peers: bool,
 }
 
 impl<T> Snowball<T>
 where
     T: Eq + Hash + Clone,
 {
-    /// Creates a new Snowball.
+    /// Creates a new Snowball which favors the item with the most votes.
     pub fn new(sample_size: u8, quorum_size: u8, decision_threshold: u8) -> Self {
+        Snowball::with_vote_counter(
+            sample_size,
+            quorum_size,
+            decision_threshold,
+            MajorityCounter,
+        )
+    }
+}
+
+impl<T, C> Snowball<T, C>
+where
+    T: Eq + Hash + Clone,
+    C: VoteCounter<T>,
+{
+    /// Creates a new Snowball which uses the given strategy to count votes.
+    pub fn with_vote_counter(
+        sample_size: u8,
+        quorum_size: u8,
+        decision_threshold: u8,
+        vote_counter: C,
+    ) -> Self {
         Snowball {
             value: None,
             done: false,
+            timed_out: false,
+            rounds: 0,
+            max_rounds: None,
             counter: 0,
             counters: HashMap::new(),
             sample_size,
             quorum_size,
             decision_threshold,
+            vote_counter,
+            require_distinct_peers: false,
+        }
+    }
+
+    /// Sets the maximum number of rounds to run before giving up.
+    pub fn with_max_rounds(mut self, max_rounds: u64) -> Self {
+        self.max_rounds = Some(max_rounds);
+        self
+    }
+
+    /// Sets whether every peer only contributes a single vote to rounds run via
+    /// [tick_from_peers](Snowball::tick_from_peers) and [poll](Snowball::poll).
+    pub fn with_distinct_peers(mut self, require_distinct_peers: bool) -> Self {
+        self.require_distinct_peers = require_distinct_peers;
+        self
+    }
+
+    /// Sets the value which is preferred before the first quorum is reached.
+    pub fn with_preference(mut self, value: T) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    /// Returns the current value.
+    pub fn value(&self) -> Option<&T> {
+        self.value.as_ref()
+    }
+
+    /// Returns whether the algorithm converged.
+    pub fn done(&self) -> bool {
+        self.done
+    }
+
+    /// Returns whether the algorithm gave up before converging.
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    /// Returns the number of rounds in which the given item was the accepted favorite.
+    pub fn item_confidence(&self, item: &T) -> u8 {
+        self.counters.get(item).copied().unwrap_or(0)
+    }
+
+    /// Samples up to `k` distinct peers where the probability of a peer being
+    /// picked is proportional to its stake. Peers without stake are never picked.
+    pub fn sample_peers_weighted<P: Clone, R: Rng + ?Sized>(
+        &self,
+        peers: &[(P, u64)],
+        k: u8,
+        rng: &mut R,
+    ) -> Result<Vec<P>, SnowballError> {
+        peers
+            .iter()
+            .try_fold(0u64, |total, (_, stake)| total.checked_add(*stake))
+            .ok_or(SnowballError::StakeOverflow)?;
+
+        let mut remaining: Vec<&(P, u64)> = peers.iter().filter(|(_, stake)| *stake > 0).collect();
+        let mut sample = Vec::with_capacity(k as usize);
+        while sample.len() < k as usize && !remaining.is_empty() {
+            // All remaining stakes are positive and their total fits into a u64 so
+            // we can safely unwrap.
+            let distribution =
+                WeightedIndex::new(remaining.iter().map(|(_, stake)| *stake)).unwrap();
+            // Remove the picked peer so that it can't be sampled twice.
+            let (peer, _) = remaining.swap_remove(distribution.sample(rng));
+            sample.push(peer.clone());
         }
+        Ok(sample)
     }
 
     /// Run one round of the Snowball algorithm.
-    pub fn tick(&mut self, votes: HashMap<T, f64>) {
-        // Return if we already settled on a value.
-        if self.done {
-            return;
+    pub fn tick(&mut self, votes: HashMap<T, u64>) -> Result<(), SnowballError> {
+        self.tick_with_abstentions(votes, 0)
+    }
+
+    /// Run one round of the Snowball algorithm where every vote is tagged with
+    /// the peer which cast it.
+    ///
+    /// If distinct peers are required, only the first vote of every peer is
+    /// counted. A round in which fewer than `quorum_size` distinct peers voted
+    /// therefore never reaches a quorum and resets the counter.
+    pub fn tick_from_peers<P: Eq + Hash>(
+        &mut self,
+        votes: Vec<(P, T)>,
+    ) -> Result<(), SnowballError> {
+        let mut peers = HashSet::new();
+        let mut counted_votes = HashMap::new();
+        for (peer, vote) in votes.into_iter() {
+            if !self.require_distinct_peers || peers.insert(peer) {
+                *counted_votes.entry(vote).or_insert(0) += 1;
+            }
         }
+        self.tick(counted_votes)
+    }
 
-        // Ensure that the denominator (number of votes) can't be less than 2.
-        let mut denom = votes.keys().len() as f64;
-        if denom < 2.0 {
-            denom = 2.0;
+    /// Run rounds with the votes returned by `vote_fn` for every round until the
+    /// algorithm converged, gave up or ran `max_rounds` rounds. Every element
+    /// returned by `vote_fn` is the vote of a single peer. Returns the number of
+    /// rounds run alongside the current value.
+    pub fn run_to_completion(
+        &mut self,
+        mut vote_fn: impl FnMut(u64) -> Vec<T>,
+        max_rounds: u64,
+    ) -> Result<(u64, Option<T>), SnowballError> {
+        let mut rounds = 0;
+        while rounds < max_rounds && !self.done && !self.timed_out {
+            let mut votes = HashMap::new();
+            for vote in vote_fn(rounds).into_iter() {
+                *votes.entry(vote).or_insert(0) += 1;
+            }
+            self.tick(votes)?;
+            rounds += 1;
         }
+        Ok((rounds, self.value.clone()))
+    }
 
-        // Get item with the majority of votes and its votes.
-        let mut favorite: Option<T> = None;
-        let mut favorite_votes: f64 = 0.0;
+    /// Run one round of the Snowball algorithm by querying `sample_size` peers
+    /// about the current value. The votes are counted like the ones passed to
+    /// [tick_from_peers](Snowball::tick_from_peers).
+    pub fn poll(&mut self, queryable: &impl Queryable<T>) -> Result<(), SnowballError> {
+        let value = self.value.as_ref().ok_or(SnowballError::NoPreference)?;
+        let votes = queryable.query(value, self.sample_size);
+        self.tick_from_peers(votes)
+    }
+
+    /// Run one round of the Snowball algorithm where peers may abstain by
+    /// voting for `None`.
+    ///
+    /// Abstentions are part of the sample, so they count toward `sample_size`,
+    /// but they never count toward the quorum of any value.
+    pub fn tick_abstaining(&mut self, votes: HashMap<Option<T>, u64>) -> Result<(), SnowballError> {
+        let mut abstentions: u64 = 0;
+        let mut value_votes = HashMap::new();
         for (item, votes) in votes.into_iter() {
-            if votes > favorite_votes {
-                favorite = Some(item);
-                favorite_votes = votes;
+            match item {
+                Some(item) => {
+                    value_votes.insert(item, votes);
+                }
+                None => abstentions = abstentions.saturating_add(votes),
+            }
+        }
+        self.tick_with_abstentions(value_votes, abstentions)
+    }
+
+    /// Run one round of the Snowball algorithm given the number of abstentions.
+    fn tick_with_abstentions(
+        &mut self,
+        votes: HashMap<T, u64>,
+        abstentions: u64,
+    ) -> Result<(), SnowballError> {
+        // Return if we already settled on a value or gave up.
+        if self.done || self.timed_out {
+            return Ok(());
+        }
+
+        // Ensure that we didn't receive more votes than we queried peers.
+        let total_votes = votes
+            .values()
+            .fold(abstentions, |total, votes| total.saturating_add(*votes));
+        if total_votes > u64::from(self.sample_size) {
+            return Err(SnowballError::SampleTooLarge);
+        }
+
+        // Give up if we exceeded the maximum number of rounds.
+        self.rounds += 1;
+        if let Some(max_rounds) = self.max_rounds {
+            if self.rounds > max_rounds {
+                self.timed_out = true;
+                return Ok(());
             }
         }
 
-        // Check if there's a quorum.
-        if favorite_votes >= (self.quorum_size as f64 * 2.0 / denom) {
-            // We have votes for favorites so we can safely unwrap.
-            let favorite = favorite.unwrap();
+        // Get the favorite item and its votes.
+        let favorite = self.vote_counter.tally(votes);
+
+        // Check if the favorite received a quorum of votes.
+        if let Some((favorite, _)) =
+            favorite.filter(|(_, favorite_votes)| self.is_quorum(*favorite_votes))
+        {
             // Store the old value so that we can use it for comparison later.
             let old_value = self.value.clone();
             // Increment the favorites counter.
-            *self.counters.entry(favorite.clone()).or_insert(0) += 1;
+            let favorite_counter = self.counters.entry(favorite.clone()).or_insert(0);
+            *favorite_counter = favorite_counter.saturating_add(1);
             // Set the current value to the favorite if its counter is higher.
             if self.value.is_none()
                 || self.counters.get(&favorite) > self.counters.get(self.value.as_ref().unwrap())
@@ -82,7 +343,7 @@ where
             }
             // Increment the counter if we've seen the favorite before.
             if Some(favorite) == old_value {
-                self.counter += 1;
+                self.counter = self.counter.saturating_add(1);
             } else {
                 self.counter = 1;
             }
@@ -95,12 +356,40 @@ where
         if self.counter > self.decision_threshold {
             self.done = true;
         }
+        Ok(())
+    }
+
+    /// Returns whether `votes` reaches `quorum_size`.
+    fn is_quorum(&self, votes: u64) -> bool {
+        votes >= u64::from(self.quorum_size)
+    }
+}
+
+/// Returns the probability that at least `quorum_size` of `sample_size` queried
+/// peers are honest if each peer is adversarial with `adversary_fraction`, i.e.
+/// the probability that the honest value reaches a quorum in a single round.
+pub fn convergence_probability(sample_size: u8, quorum_size: u8, adversary_fraction: f64) -> f64 {
+    let honest_fraction = 1.0 - adversary_fraction;
+    let mut probability = 0.0;
+    // Binomial coefficient of the sample size and the number of honest peers.
+    let mut coefficient = 1.0;
+    for honest in 0..=sample_size {
+        if honest > 0 {
+            coefficient *= (sample_size - honest + 1) as f64 / honest as f64;
+        }
+        if honest >= quorum_size {
+            probability += coefficient
+                * honest_fraction.powi(honest as i32)
+                * adversary_fraction.powi((sample_size - honest) as i32);
+        }
     }
+    probability
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
 
     #[derive(Debug, PartialEq, Eq, Hash, Clone)]
     enum Color {
@@ -122,26 +411,89 @@ mod tests {
         let expected: Snowball<()> = Snowball {
             value: None,
             done: false,
+            timed_out: false,
+            rounds: 0,
+            max_rounds: None,
             counter: 0,
             counters: HashMap::new(),
             sample_size: 5,
             quorum_size: 4,
             decision_threshold: 3,
+            vote_counter: MajorityCounter,
+            require_distinct_peers: false,
         };
 
         assert_eq!(snowball, expected);
     }
 
+    #[test]
+    fn majority_counter() {
+        let mut votes = HashMap::new();
+        assert_eq!(MajorityCounter.tally(votes.clone()), None);
+
+        votes.insert(Color::Red, 1);
+        votes.insert(Color::Green, 3);
+        votes.insert(Color::Blue, 1);
+        assert_eq!(MajorityCounter.tally(votes), Some((Color::Green, 3)));
+    }
+
+    #[test]
+    fn binary_counter() {
+        let mut snowball = Snowball::with_vote_counter(5, 3, 3, BinaryCounter);
+        let mut votes = HashMap::new();
+
+        // A tie doesn't produce a favorite.
+        votes.insert(true, 2);
+        votes.insert(false, 2);
+        snowball.tick(votes.clone()).unwrap();
+        assert_eq!(snowball.counter, 0);
+        assert_eq!(snowball.value, None);
+
+        votes.insert(true, 3);
+        for _ in 0..4 {
+            snowball.tick(votes.clone()).unwrap();
+        }
+        assert_eq!(snowball.done(), true);
+        assert_eq!(snowball.value(), Some(&true));
+    }
+
+    #[test]
+    fn quorum_boundary() {
+        for sample_size in 1..=u8::MAX {
+            for quorum_size in 1..=sample_size {
+                let snowball: Snowball<()> = Snowball::new(sample_size, quorum_size, 3);
+                assert!(snowball.is_quorum(u64::from(quorum_size)));
+                assert!(!snowball.is_quorum(u64::from(quorum_size) - 1));
+            }
+        }
+
+        // Exactly a quorum of votes is accepted.
+        let mut snowball = Snowball::new(7, 3, 3);
+        let mut votes = HashMap::new();
+        votes.insert(Color::Red, 3);
+        votes.insert(Color::Green, 2);
+        snowball.tick(votes).unwrap();
+        assert_eq!(snowball.counter, 1);
+        assert_eq!(snowball.value, Some(Color::Red));
+
+        // Votes of the whole sample are exactly a quorum of the sample size.
+        let mut snowball = Snowball::new(10, 10, 3);
+        snowball
+            .tick_from_peers((0..10).map(|peer| (peer, Color::Red)).collect())
+            .unwrap();
+        assert_eq!(snowball.counter, 1);
+        assert_eq!(snowball.value, Some(Color::Red));
+    }
+
     #[test]
     fn track_successes() {
         let mut snowball = get_snowball();
         let mut votes = HashMap::new();
 
-        votes.insert(Color::Red, 3.0);
-        votes.insert(Color::Green, 1.0);
-        votes.insert(Color::Blue, 1.0);
+        votes.insert(Color::Red, 4);
+        votes.insert(Color::Green, 1);
 
-        snowball.tick(votes);
+        snowball.tick(votes).unwrap();
         assert_eq!(snowball.counter, 1);
         assert_eq!(snowball.done, false);
         assert_eq!(snowball.value, Some(Color::Red));
@@ -152,21 +504,20 @@ mod tests {
         let mut snowball = get_snowball();
         let mut votes = HashMap::new();
 
-        votes.insert(Color::Red, 3.0);
-        votes.insert(Color::Green, 1.0);
-        votes.insert(Color::Blue, 1.0);
+        votes.insert(Color::Red, 4);
+        votes.insert(Color::Green, 1);
 
-        snowball.tick(votes.clone());
+        snowball.tick(votes.clone()).unwrap();
         assert_eq!(snowball.counter, 1);
         assert_eq!(snowball.done, false);
         assert_eq!(snowball.value, Some(Color::Red));
 
         votes.clear();
 
-        votes.insert(Color::Red, 2.0);
-        votes.insert(Color::Green, 2.0);
-        votes.insert(Color::Blue, 1.0);
-        snowball.tick(votes);
+        votes.insert(Color::Red, 2);
+        votes.insert(Color::Green, 2);
+        votes.insert(Color::Blue, 1);
+        snowball.tick(votes).unwrap();
         assert_eq!(snowball.counter, 0);
         assert_eq!(snowball.done, false);
         assert_eq!(snowball.value, Some(Color::Red));
@@ -177,80 +528,372 @@ mod tests {
         let mut snowball = get_snowball();
         let mut votes = HashMap::new();
 
-        votes.insert(Color::Red, 3.0);
-        votes.insert(Color::Green, 1.0);
-        votes.insert(Color::Blue, 1.0);
+        votes.insert(Color::Red, 4);
+        votes.insert(Color::Green, 1);
 
-        snowball.tick(votes.clone());
+        snowball.tick(votes.clone()).unwrap();
         assert_eq!(snowball.counter, 1);
         assert_eq!(snowball.done, false);
         assert_eq!(snowball.value, Some(Color::Red));
 
         votes.clear();
 
-        votes.insert(Color::Red, 1.0);
-        votes.insert(Color::Green, 1.0);
-        votes.insert(Color::Blue, 3.0);
+        votes.insert(Color::Red, 1);
+        votes.insert(Color::Blue, 4);
 
-        snowball.tick(votes.clone());
+        snowball.tick(votes.clone()).unwrap();
         assert_eq!(snowball.counter, 1);
         assert_eq!(snowball.done, false);
         assert_eq!(snowball.value, Some(Color::Red));
 
         votes.clear();
 
-        votes.insert(Color::Red, 1.0);
-        votes.insert(Color::Green, 1.0);
-        votes.insert(Color::Blue, 3.0);
+        votes.insert(Color::Red, 1);
+        votes.insert(Color::Blue, 4);
 
-        snowball.tick(votes.clone());
+        snowball.tick(votes.clone()).unwrap();
         assert_eq!(snowball.counter, 1);
         assert_eq!(snowball.done, false);
         assert_eq!(snowball.value, Some(Color::Blue));
 
         votes.clear();
 
-        votes.insert(Color::Red, 1.0);
-        votes.insert(Color::Green, 1.0);
-        votes.insert(Color::Blue, 3.0);
+        votes.insert(Color::Red, 1);
+        votes.insert(Color::Blue, 4);
 
-        snowball.tick(votes);
+        snowball.tick(votes).unwrap();
         assert_eq!(snowball.counter, 2);
         assert_eq!(snowball.done, false);
         assert_eq!(snowball.value, Some(Color::Blue));
     }
 
+    #[test]
+    fn item_confidence() {
+        let mut snowball = get_snowball();
+        let mut votes = HashMap::new();
+
+        votes.insert(Color::Red, 4);
+        votes.insert(Color::Blue, 1);
+        snowball.tick(votes.clone()).unwrap();
+
+        votes.insert(Color::Red, 1);
+        votes.insert(Color::Blue, 4);
+        snowball.tick(votes.clone()).unwrap();
+        snowball.tick(votes.clone()).unwrap();
+
+        // Rounds without a quorum don't change any counter.
+        votes.insert(Color::Blue, 3);
+        snowball.tick(votes).unwrap();
+
+        assert_eq!(snowball.item_confidence(&Color::Red), 1);
+        assert_eq!(snowball.item_confidence(&Color::Blue), 2);
+        assert_eq!(snowball.item_confidence(&Color::Green), 0);
+        assert_eq!(snowball.value(), Some(&Color::Blue));
+    }
+
     #[test]
     fn convergence() {
         let mut snowball = get_snowball();
         let mut votes = HashMap::new();
 
-        votes.insert(Color::Red, 3.0);
-        votes.insert(Color::Green, 1.0);
-        votes.insert(Color::Blue, 1.0);
+        votes.insert(Color::Red, 4);
+        votes.insert(Color::Green, 1);
 
         // 1st round
-        snowball.tick(votes.clone());
+        snowball.tick(votes.clone()).unwrap();
         assert_eq!(snowball.counter, 1);
         assert_eq!(snowball.done, false);
         assert_eq!(snowball.value, Some(Color::Red));
 
         // 2nd round
-        snowball.tick(votes.clone());
+        snowball.tick(votes.clone()).unwrap();
         assert_eq!(snowball.counter, 2);
         assert_eq!(snowball.done, false);
         assert_eq!(snowball.value, Some(Color::Red));
 
         // 3rd round
-        snowball.tick(votes.clone());
+        snowball.tick(votes.clone()).unwrap();
         assert_eq!(snowball.counter, 3);
         assert_eq!(snowball.done, false);
         assert_eq!(snowball.value, Some(Color::Red));
 
         // 4th round
-        snowball.tick(votes);
+        snowball.tick(votes).unwrap();
         assert_eq!(snowball.counter, 4);
         assert_eq!(snowball.done, true);
         assert_eq!(snowball.value, Some(Color::Red));
     }
+
+    #[test]
+    fn run_to_completion() {
+        let votes = |red: usize, blue: usize| {
+            let mut votes = vec![Color::Red; red];
+            votes.extend(vec![Color::Blue; blue]);
+            votes
+        };
+
+        // The majority flips from Red to Blue after 2 rounds.
+        let mut snowball = get_snowball();
+        let schedule = |round: u64| if round < 2 { votes(4, 1) } else { votes(1, 4) };
+        let result = snowball.run_to_completion(schedule, 100);
+        assert_eq!(result, Ok((8, Some(Color::Blue))));
+        assert_eq!(snowball.done(), true);
+
+        // The majority oscillates between Red and Blue every round.
+        let mut snowball = get_snowball();
+        let schedule = |round: u64| {
+            if round % 2 == 0 {
+                votes(4, 1)
+            } else {
+                votes(1, 4)
+            }
+        };
+        let result = snowball.run_to_completion(schedule, 20);
+        assert_eq!(result, Ok((20, Some(Color::Red))));
+        assert_eq!(snowball.done(), false);
+    }
+
+    #[test]
+    fn poll() {
+        /// Peers which mostly prefer Red.
+        struct RedMajority;
+
+        impl Queryable<Color> for RedMajority {
+            type PeerId = u8;
+
+            fn query(&self, _item: &Color, k: u8) -> Vec<(u8, Color)> {
+                let mut votes: Vec<(u8, Color)> = (1..k).map(|peer| (peer, Color::Red)).collect();
+                votes.push((k, Color::Blue));
+                votes
+            }
+        }
+
+        /// A single peer which answers every query on its own.
+        struct SinglePeer;
+
+        impl Queryable<Color> for SinglePeer {
+            type PeerId = u8;
+
+            fn query(&self, _item: &Color, k: u8) -> Vec<(u8, Color)> {
+                vec![(1, Color::Red); k as usize]
+            }
+        }
+
+        let mut snowball: Snowball<Color> = get_snowball();
+        assert_eq!(
+            snowball.poll(&RedMajority),
+            Err(SnowballError::NoPreference)
+        );
+
+        let mut snowball = get_snowball().with_preference(Color::Blue);
+        for _ in 0..4 {
+            assert_eq!(snowball.done(), false);
+            snowball.poll(&RedMajority).unwrap();
+        }
+        assert_eq!(snowball.done(), true);
+        assert_eq!(snowball.value(), Some(&Color::Red));
+
+        // Polling counts every peer once if distinct peers are required.
+        let mut snowball = get_snowball()
+            .with_preference(Color::Blue)
+            .with_distinct_peers(true);
+        snowball.poll(&SinglePeer).unwrap();
+        assert_eq!(snowball.counter, 0);
+        assert_eq!(snowball.value(), Some(&Color::Blue));
+    }
+
+    #[test]
+    fn tick_from_peers() {
+        let duplicate_votes = vec![(1, Color::Red); 4];
+        let distinct_votes: Vec<(u8, Color)> = (1..=4).map(|peer| (peer, Color::Red)).collect();
+
+        // Every vote counts unless distinct peers are required.
+        let mut snowball = get_snowball();
+        snowball.tick_from_peers(duplicate_votes.clone()).unwrap();
+        assert_eq!(snowball.counter, 1);
+        assert_eq!(snowball.value, Some(Color::Red));
+
+        let mut snowball = get_snowball().with_distinct_peers(true);
+        snowball.tick_from_peers(distinct_votes.clone()).unwrap();
+        assert_eq!(snowball.counter, 1);
+        assert_eq!(snowball.value, Some(Color::Red));
+
+        // Votes of a single peer don't reach a quorum and reset the counter.
+        snowball.tick_from_peers(duplicate_votes).unwrap();
+        assert_eq!(snowball.counter, 0);
+        assert_eq!(snowball.item_confidence(&Color::Red), 1);
+
+        snowball.tick_from_peers(distinct_votes).unwrap();
+        assert_eq!(snowball.counter, 1);
+        assert_eq!(snowball.item_confidence(&Color::Red), 2);
+    }
+
+    #[test]
+    fn reject_oversized_sample() {
+        let mut snowball = get_snowball();
+        let mut votes = HashMap::new();
+
+        votes.insert(Color::Red, 4);
+        votes.insert(Color::Green, 2);
+
+        assert_eq!(snowball.tick(votes), Err(SnowballError::SampleTooLarge));
+        assert_eq!(snowball.counter, 0);
+        assert_eq!(snowball.value, None);
+    }
+
+    #[test]
+    fn quorum_relative_to_sample_size() {
+        let mut snowball = Snowball::new(10, 6, 3);
+        let mut votes = HashMap::new();
+
+        // 5 out of 10 sampled peers aren't a quorum.
+        votes.insert(Color::Red, 5);
+        votes.insert(Color::Green, 1);
+        snowball.tick(votes.clone()).unwrap();
+        assert_eq!(snowball.counter, 0);
+        assert_eq!(snowball.value, None);
+
+        votes.clear();
+
+        // 6 out of 10 sampled peers are a quorum.
+        votes.insert(Color::Red, 6);
+        snowball.tick(votes).unwrap();
+        assert_eq!(snowball.counter, 1);
+        assert_eq!(snowball.value, Some(Color::Red));
+    }
+
+    #[test]
+    fn abstentions_prevent_quorum() {
+        let mut snowball = get_snowball();
+        let mut votes = HashMap::new();
+
+        votes.insert(Some(Color::Red), 4);
+        votes.insert(None, 1);
+        snowball.tick_abstaining(votes.clone()).unwrap();
+        assert_eq!(snowball.counter, 1);
+        assert_eq!(snowball.value, Some(Color::Red));
+
+        votes.clear();
+
+        // The quorum would be reached if the abstaining peers voted for Red.
+        votes.insert(Some(Color::Red), 3);
+        votes.insert(None, 2);
+        snowball.tick_abstaining(votes.clone()).unwrap();
+        assert_eq!(snowball.counter, 0);
+        assert_eq!(snowball.value, Some(Color::Red));
+
+        // Abstentions count toward the sample size.
+        votes.insert(Some(Color::Blue), 1);
+        assert_eq!(
+            snowball.tick_abstaining(votes),
+            Err(SnowballError::SampleTooLarge)
+        );
+    }
+
+    #[test]
+    fn max_rounds() {
+        let mut snowball = get_snowball().with_max_rounds(3);
+        let mut votes = HashMap::new();
+
+        votes.insert(Color::Red, 2);
+        votes.insert(Color::Green, 2);
+        votes.insert(Color::Blue, 1);
+
+        // 1st - 3rd round
+        for _ in 0..3 {
+            snowball.tick(votes.clone()).unwrap();
+            assert_eq!(snowball.timed_out(), false);
+        }
+
+        // 4th round
+        snowball.tick(votes).unwrap();
+        assert_eq!(snowball.timed_out(), true);
+        assert_eq!(snowball.done(), false);
+
+        // A timed out Snowball doesn't accept any more votes.
+        let mut votes = HashMap::new();
+        votes.insert(Color::Red, 5);
+        snowball.tick(votes).unwrap();
+        assert_eq!(snowball.counter, 0);
+        assert_eq!(snowball.value, None);
+    }
+
+    #[test]
+    fn convergence_probability() {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+
+        assert!(close(super::convergence_probability(5, 3, 0.0), 1.0));
+        assert!(close(super::convergence_probability(5, 3, 1.0), 0.0));
+        // (10 + 5 + 1) / 32 samples contain at least 3 honest peers.
+        assert!(close(super::convergence_probability(5, 3, 0.5), 0.5));
+        // 1 - 0.25^2 of the samples contain at least 1 honest peer.
+        assert!(close(super::convergence_probability(2, 1, 0.25), 0.9375));
+        // 0.8^4 + 4 * 0.8^3 * 0.2
+        assert!(close(super::convergence_probability(4, 3, 0.2), 0.8192));
+    }
+
+    #[test]
+    fn sample_peers_weighted() {
+        let snowball: Snowball<()> = get_snowball();
+        let peers = vec![("alice", 1), ("bob", 1), ("carol", 98), ("dave", 0)];
+        let mut rng = StdRng::seed_from_u64(42);
+
+        // The high-stake peer should be picked most of the time.
+        let mut carol = 0;
+        for _ in 0..1000 {
+            let sample = snowball.sample_peers_weighted(&peers, 1, &mut rng).unwrap();
+            assert_eq!(sample.len(), 1);
+            if sample[0] == "carol" {
+                carol += 1;
+            }
+        }
+        assert!(carol > 900);
+
+        // Peers are never picked twice and peers without stake are never picked.
+        for _ in 0..100 {
+            let mut sample = snowball.sample_peers_weighted(&peers, 4, &mut rng).unwrap();
+            sample.sort();
+            assert_eq!(sample, vec!["alice", "bob", "carol"]);
+        }
+
+        // Stakes which don't add up to a u64 are rejected rather than overflowing.
+        let peers = vec![("alice", u64::MAX), ("bob", 1)];
+        assert_eq!(
+            snowball.sample_peers_weighted(&peers, 1, &mut rng),
+            Err(SnowballError::StakeOverflow)
+        );
+        let peers = vec![("alice", u64::MAX - 1), ("bob", 1)];
+        assert_eq!(
+            snowball
+                .sample_peers_weighted(&peers, 2, &mut rng)
+                .map(|sample| sample.len()),
+            Ok(2)
+        );
+    }
+
+    #[test]
+    fn saturating_counters() {
+        let mut votes = HashMap::new();
+        votes.insert(Color::Red, 4);
+        votes.insert(Color::Green, 1);
+
+        // A decision threshold of `u8::MAX` can never be reached.
+        let mut snowball = Snowball::new(5, 4, u8::MAX);
+        for _ in 0..300 {
+            snowball.tick(votes.clone()).unwrap();
+        }
+        assert_eq!(snowball.counter, u8::MAX);
+        assert_eq!(snowball.counters.get(&Color::Red), Some(&u8::MAX));
+        assert_eq!(snowball.done, false);
+        assert_eq!(snowball.value, Some(Color::Red));
+
+        // The highest reachable decision threshold finalizes and stays finalized.
+        let mut snowball = Snowball::new(5, 4, u8::MAX - 1);
+        for _ in 0..300 {
+            snowball.tick(votes.clone()).unwrap();
+        }
+        assert_eq!(snowball.counter, u8::MAX);
+        assert_eq!(snowball.done, true);
+        assert_eq!(snowball.value, Some(Color::Red));
+    }
 }
@@ -0,0 +1,222 @@
+use std::convert::TryFrom;
+
+use crate::{
+    block::Block,
+    node::{Node, NodeBuilder, NodeError},
+    snowball::{Queryable, Snowball, SnowballError},
+    transaction::Transaction,
+    utils::Keccak256,
+};
+
+/// Number of consecutive votes required to finalize a Block.
+const DECISION_THRESHOLD: u8 = 3;
+
+/// Maximum number of Snowball rounds a Node runs before giving up.
+const MAX_ROUNDS: usize = 100;
+
+/// Errors which can occur while the [Network](crate::testing::Network) runs a round.
+#[derive(Debug, PartialEq)]
+pub enum NetworkError {
+    /// A Node failed to run a round of Snowball.
+    Snowball(SnowballError),
+    /// A Node rejected the Block it decided on.
+    Node(NodeError),
+}
+
+/// An in-memory Network of [Nodes](crate::node::Node) which relays
+/// Transactions and Blocks synchronously.
+pub struct Network {
+    /// Nodes connected to the Network.
+    nodes: Vec<Node>,
+    /// Slot of the next round, used to select the leader which proposes a Block.
+    slot: u64,
+}
+
+impl Network {
+    /// Creates a new Network with the given number of Nodes. Every Node is
+    /// registered as a validator with every other Node.
+    pub fn new(size: usize) -> Self {
+        let public_keys: Vec<Vec<u8>> = (0..size as u64)
+            .map(|index| index.to_be_bytes().to_vec())
+            .collect();
+        let nodes = public_keys
+            .iter()
+            .map(|public_key| {
+                // Public keys are 8 bytes long, so they always fit into a Block.
+                let mut node = NodeBuilder::new()
+                    .public_key(public_key.clone())
+                    .unwrap()
+                    .build();
+                for validator in public_keys.iter() {
+                    node.register_validator(validator.clone(), 1);
+                }
+                node
+            })
+            .collect();
+        Network { nodes, slot: 0 }
+    }
+
+    /// Returns a reference to the Nodes connected to the Network.
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Relay a Transaction to every Node in the Network.
+    pub fn broadcast_transaction(&mut self, transaction: Transaction) {
+        for node in self.nodes.iter_mut() {
+            node.add_transaction(transaction.clone());
+        }
+    }
+
+    /// Run one round in which every Node proposes a Block and prefers the proposal
+    /// of the leader it selected for the slot. Every Node then runs Snowball by
+    /// polling the preferences of the other Nodes and finalizes the Block it
+    /// decided on. Returns the id of the finalized Block.
+    ///
+    /// Nodes only finalize Blocks their peers agree on, so a Network needs at
+    /// least two Nodes to make progress.
+    pub fn step(&mut self) -> Result<Option<Keccak256>, NetworkError> {
+        let slot = self.slot;
+        self.slot += 1;
+
+        let proposals: Vec<Option<Block>> =
+            self.nodes.iter().map(|node| node.propose_block()).collect();
+        // The sample size saturates so that large Networks only query a subset of their Nodes.
+        let sample_size = u8::try_from(self.nodes.len().saturating_sub(1)).unwrap_or(u8::MAX);
+        let quorum_size = sample_size / 2 + 1;
+        let mut snowballs: Vec<Snowball<Keccak256>> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                let snowball = Snowball::new(sample_size, quorum_size, DECISION_THRESHOLD)
+                    .with_distinct_peers(true);
+                let leader = node.select_leader(slot);
+                let preference = self
+                    .nodes
+                    .iter()
+                    .zip(proposals.iter())
+                    .find(|(other, _)| leader.as_deref() == Some(other.public_key()))
+                    .and_then(|(_, proposal)| proposal.as_ref());
+                match preference {
+                    Some(block) => snowball.with_preference(block.id.clone()),
+                    None => snowball,
+                }
+            })
+            .collect();
+
+        // Nodes without a preference neither poll nor get polled.
+        let polling =
+            |snowball: &Snowball<Keccak256>| !snowball.done() && snowball.value().is_some();
+        for _ in 0..MAX_ROUNDS {
+            if !snowballs.iter().any(polling) {
+                break;
+            }
+            let preferences: Vec<Option<Keccak256>> = snowballs
+                .iter()
+                .map(|snowball| snowball.value().cloned())
+                .collect();
+            for (querier, snowball) in snowballs.iter_mut().enumerate() {
+                if polling(snowball) {
+                    let peers = Peers {
+                        querier,
+                        preferences: &preferences,
+                    };
+                    snowball.poll(&peers).map_err(NetworkError::Snowball)?;
+                }
+            }
+        }
+
+        let mut finalized = None;
+        for (node, snowball) in self.nodes.iter_mut().zip(snowballs.iter()) {
+            let decided = snowball.value().filter(|_| snowball.done());
+            // The decided value is always one of the proposed Block ids.
+            let block = decided.and_then(|block_id| {
+                proposals
+                    .iter()
+                    .flatten()
+                    .find(|block| &block.id == block_id)
+            });
+            if let Some(block) = block {
+                node.apply_block(block.clone())
+                    .map_err(NetworkError::Node)?;
+                finalized = Some(block.id.clone());
+            }
+        }
+        Ok(finalized)
+    }
+}
+
+/// The preferences of all Nodes in a Snowball round as seen by the querying Node.
+struct Peers<'a> {
+    /// Position of the querying Node in the Network.
+    querier: usize,
+    /// Preference of every Node by its position in the Network.
+    preferences: &'a [Option<Keccak256>],
+}
+
+impl Queryable<Keccak256> for Peers<'_> {
+    type PeerId = usize;
+
+    fn query(&self, _item: &Keccak256, k: u8) -> Vec<(usize, Keccak256)> {
+        self.preferences
+            .iter()
+            .enumerate()
+            .filter(|(peer, _)| *peer != self.querier)
+            .filter_map(|(peer, preference)| preference.clone().map(|value| (peer, value)))
+            .take(k as usize)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn step_without_transactions() {
+        let mut network = Network::new(3);
+
+        assert_eq!(network.step(), Ok(None));
+        assert!(network
+            .nodes()
+            .iter()
+            .all(|node| node.chain().height().is_none()));
+    }
+
+    #[test]
+    fn nodes_converge() {
+        let mut network = Network::new(3);
+
+        // 1st Step: Relay Transactions and finalize a Block.
+        network.broadcast_transaction(Transaction::new(vec![0, 1, 2, 3, 4], 1));
+        network.broadcast_transaction(Transaction::new(vec![5, 6, 7, 8, 9], 1));
+        let first_block_id = network.step().unwrap();
+        assert!(first_block_id.is_some());
+
+        // 2nd Step: Relay another Transaction and finalize a Block.
+        network.broadcast_transaction(Transaction::new(vec![0, 1, 2, 3, 4], 2));
+        let second_block_id = network.step().unwrap();
+        assert!(second_block_id.is_some());
+        assert_ne!(first_block_id, second_block_id);
+
+        // All Nodes should've reached the same Chain tip.
+        for node in network.nodes() {
+            assert_eq!(node.chain().height(), Some(1));
+            assert_eq!(node.chain().tip_id(), second_block_id.as_ref());
+            assert_eq!(node.propose_block(), None);
+        }
+    }
+
+    #[test]
+    fn query_other_nodes() {
+        let preferences = vec![Some([1; 32].into()), None, Some([3; 32].into())];
+        let peers = Peers {
+            querier: 0,
+            preferences: &preferences,
+        };
+
+        // The querier and Nodes without a preference don't vote.
+        let votes = peers.query(&[1; 32].into(), 2);
+        assert_eq!(votes, vec![(2, [3; 32].into())]);
+    }
+}
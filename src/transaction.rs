@@ -1,8 +1,10 @@
+use std::convert::TryFrom;
+
 use bincode;
 use serde::{Deserialize, Serialize};
 
 use super::utils;
-use super::utils::{BinEncoding, Keccak256, Sender};
+use super::utils::{BinEncoding, Domain, Keccak256, Sender};
 
 /// A Transaction which includes a reference to its sender and a nonce.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -22,10 +24,84 @@ impl Transaction {
         Transaction { id, sender, nonce }
     }
 
-    /// Generates a unique Transaction id.
+    /// Creates a Transaction from its parts without recomputing its id.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn from_parts(id: Keccak256, sender: Sender, nonce: u64) -> Self {
+        Transaction { id, sender, nonce }
+    }
+
+    /// Derives the sender of Transactions created by the owner of the given public
+    /// key, i.e. the Keccak256 hash of the public key.
+    pub fn derive_sender(public_key: &[u8]) -> Sender {
+        utils::hash(Domain::Sender, public_key).into()
+    }
+
+    /// Returns a reference to the entity which created the Transaction.
+    pub fn sender(&self) -> &Sender {
+        &self.sender
+    }
+
+    /// Returns the nonce of the Transaction.
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    /// Returns whether both Transactions have the same contents regardless of
+    /// their ids.
+    pub fn same_content(&self, other: &Transaction) -> bool {
+        self.sender == other.sender && self.nonce == other.nonce
+    }
+
+    /// Returns whether the Transactions id matches its contents.
+    pub fn validate(&self) -> bool {
+        let id = Transaction::generate_id(&self.sender, &self.nonce);
+        utils::ct_eq(self.id.as_bytes(), id.as_bytes())
+    }
+
+    /// Generates a unique Transaction id by hashing its canonical encoding in the
+    /// Transaction domain.
     pub fn generate_id(sender: &Sender, nonce: &u64) -> Keccak256 {
-        let serialized = Transaction::serialize(&sender, &nonce);
-        utils::hash(&serialized)
+        let encoded = Transaction::canonical_encoding(sender, nonce);
+        utils::hash_tx(&encoded)
+    }
+
+    /// Encodes the Transaction data for generating its id. The encoding is
+    /// independent of the binary representation and consists of the length of
+    /// the sender as a big-endian `u64`, the sender and the nonce as a big-endian
+    /// `u64`.
+    pub fn canonical_encoding(sender: &Sender, nonce: &u64) -> Vec<u8> {
+        let mut encoded = Vec::with_capacity(sender.len() + 16);
+        encoded.extend_from_slice(&(sender.len() as u64).to_be_bytes());
+        encoded.extend_from_slice(sender);
+        encoded.extend_from_slice(&nonce.to_be_bytes());
+        encoded
+    }
+
+    /// Encodes the Transaction with big-endian fields. Only meant for
+    /// interoperability with systems which expect big-endian data. The id is
+    /// followed by the [canonical encoding](Transaction::canonical_encoding).
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        let mut bytes = self.id.as_bytes().to_vec();
+        bytes.extend(Transaction::canonical_encoding(&self.sender, &self.nonce));
+        bytes
+    }
+
+    /// Decodes a Transaction encoded with [to_bytes_be](Transaction::to_bytes_be).
+    /// Returns `None` if the bytes aren't a valid encoding or if the encoded id
+    /// doesn't match the Transaction data.
+    pub fn from_bytes_be(bytes: &[u8]) -> Option<Transaction> {
+        let read_u64 = |bytes: &[u8]| <[u8; 8]>::try_from(bytes).ok().map(u64::from_be_bytes);
+
+        let id = Keccak256::try_from(bytes.get(..32)?).ok()?;
+        let sender_len = read_u64(bytes.get(32..40)?)? as usize;
+        let sender_end = 40usize.checked_add(sender_len)?;
+        let sender = bytes.get(40..sender_end)?.to_vec();
+        let nonce = read_u64(bytes.get(sender_end..)?)?;
+        let tx = Transaction { id, sender, nonce };
+        if !tx.validate() {
+            return None;
+        }
+        Some(tx)
     }
 
     /// Serializes the Transaction data into a binary representation.
@@ -49,10 +125,11 @@ mod tests {
     fn new_transaction() {
         let tx = Transaction::new(vec![1, 2, 3, 4, 5], 42);
         let expected = Transaction {
-            id: vec![
-                242, 173, 79, 62, 149, 64, 34, 43, 218, 41, 24, 9, 145, 148, 96, 195, 129, 80, 125,
-                126, 255, 231, 209, 59, 221, 242, 186, 41, 33, 28, 79, 50,
-            ],
+            id: [
+                66, 0, 91, 62, 57, 119, 193, 251, 69, 181, 140, 5, 43, 12, 243, 132, 241, 73, 203,
+                23, 231, 87, 142, 49, 72, 63, 81, 196, 210, 67, 61, 12,
+            ]
+            .into(),
             sender: vec![1, 2, 3, 4, 5],
             nonce: 42,
         };
@@ -60,6 +137,90 @@ mod tests {
         assert_eq!(tx, expected);
     }
 
+    #[test]
+    fn derive_sender() {
+        let sender = Transaction::derive_sender(&[1, 2, 3, 4, 5]);
+        assert_eq!(
+            sender,
+            vec![
+                143, 45, 59, 33, 100, 237, 59, 208, 207, 120, 226, 143, 70, 198, 86, 33, 158, 206,
+                184, 172, 145, 82, 131, 121, 83, 224, 6, 83, 253, 192, 3, 206
+            ]
+        );
+        assert_eq!(Transaction::derive_sender(&[1, 2, 3, 4, 5]), sender);
+    }
+
+    #[test]
+    fn canonical_encoding() {
+        let sender = vec![1, 2, 3, 4, 5];
+        let encoded = Transaction::canonical_encoding(&sender, &42);
+        assert_eq!(
+            encoded,
+            vec![0, 0, 0, 0, 0, 0, 0, 5, 1, 2, 3, 4, 5, 0, 0, 0, 0, 0, 0, 0, 42]
+        );
+
+        let tx = Transaction::new(sender, 42);
+        assert_eq!(tx.id, utils::hash_tx(&encoded));
+    }
+
+    #[test]
+    fn to_bytes_be() {
+        let tx = Transaction::new(vec![1, 2, 3, 4, 5], 42);
+
+        let bytes = tx.to_bytes_be();
+        assert_eq!(&bytes[..32], tx.id.as_bytes());
+        assert_eq!(
+            &bytes[32..],
+            &[0, 0, 0, 0, 0, 0, 0, 5, 1, 2, 3, 4, 5, 0, 0, 0, 0, 0, 0, 0, 42]
+        );
+        assert_eq!(Transaction::from_bytes_be(&bytes), Some(tx));
+
+        // Truncated or oversized bytes can't be decoded.
+        assert_eq!(Transaction::from_bytes_be(&bytes[..bytes.len() - 1]), None);
+        assert_eq!(
+            Transaction::from_bytes_be(&[bytes.clone(), vec![0]].concat()),
+            None
+        );
+
+        // Bytes with a tampered id can't be decoded.
+        let mut tampered = bytes;
+        tampered[0] ^= 1;
+        assert_eq!(Transaction::from_bytes_be(&tampered), None);
+    }
+
+    #[test]
+    fn validate() {
+        let mut tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        assert!(tx.validate());
+
+        // Tamper with the Transaction id
+        tx.id = [0; 32].into();
+        assert!(!tx.validate());
+    }
+
+    #[test]
+    fn same_content() {
+        let tx = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        let tampered = Transaction::from_parts([0; 32].into(), vec![0, 1, 2, 3, 4], 1);
+        assert!(tx.same_content(&tampered));
+        assert_ne!(tx, tampered);
+
+        let other = Transaction::new(vec![0, 1, 2, 3, 4], 2);
+        assert!(!tx.same_content(&other));
+    }
+
+    #[test]
+    fn from_parts() {
+        let tx = Transaction::from_parts([0; 32].into(), vec![0, 1, 2, 3, 4], 1);
+        assert_eq!(tx.sender(), &vec![0, 1, 2, 3, 4]);
+        assert_eq!(tx.nonce(), 1);
+        assert!(!tx.validate());
+
+        let valid = Transaction::new(vec![0, 1, 2, 3, 4], 1);
+        let tx = Transaction::from_parts(valid.id.clone(), vec![0, 1, 2, 3, 4], 1);
+        assert_eq!(tx, valid);
+    }
+
     #[test]
     fn serde() {
         let sender = vec![0, 1, 2, 3, 4];
@@ -1,3 +1,6 @@
+use std::convert::TryFrom;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use sha3::Digest;
 
 /// Dummy trait used to map a generic type to a u8.
@@ -12,15 +15,300 @@ impl<T> AlwaysU8 for T {
 /// A types binary encoding.
 pub(crate) type BinEncoding<T> = Vec<<T as AlwaysU8>::Type>;
 
+/// A 256 bit hash which can't be mixed up with arbitrary binary data.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Hash256([u8; 32]);
+
+impl Hash256 {
+    /// Returns the bytes of the hash.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<[u8; 32]> for Hash256 {
+    fn from(bytes: [u8; 32]) -> Self {
+        Hash256(bytes)
+    }
+}
+
+impl TryFrom<&[u8]> for Hash256 {
+    type Error = std::array::TryFromSliceError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        <[u8; 32]>::try_from(bytes).map(Hash256)
+    }
+}
+
+impl From<Hash256> for Vec<u8> {
+    fn from(hash: Hash256) -> Self {
+        hash.0.to_vec()
+    }
+}
+
+impl AsRef<[u8]> for Hash256 {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+// Hashes are encoded just like a `Vec<u8>` so that encodings (and therefore ids)
+// stay the same as when hashes were stored on the heap.
+impl Serialize for Hash256 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0[..].serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Hash256 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = Vec::<u8>::deserialize(deserializer)?;
+        Hash256::try_from(&bytes[..])
+            .map_err(|_| de::Error::invalid_length(bytes.len(), &"32 bytes"))
+    }
+}
+
+/// A reference to a [Hash256] which is encoded as a fixed-size array, i.e.
+/// without the length prefix of the default encoding.
+pub(crate) struct FixedHash<'a>(pub &'a Hash256);
+
+impl Serialize for FixedHash<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.0).0.serialize(serializer)
+    }
+}
+
 // A Keccak256 hash.
-pub(crate) type Keccak256 = Vec<u8>;
+pub(crate) type Keccak256 = Hash256;
 
 // A Keccak256 hash of a senders public key.
-pub(crate) type Sender = Keccak256;
+pub(crate) type Sender = Vec<u8>;
 
-/// Creates a Keccak256 hash of the given data.
-pub(crate) fn hash<T: AsRef<[u8]>>(data: T) -> Keccak256 {
+/// Purposes data is hashed for. The data of every domain is prefixed with a
+/// distinct tag and no tag is a prefix of another one, so data hashed in one
+/// domain never hashes to the same value as data hashed in another domain.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Domain {
+    /// Ids of [Transactions](crate::transaction::Transaction).
+    Transaction,
+    /// Ids of [Blocks](crate::block::Block).
+    Block,
+    /// Senders derived from public keys.
+    Sender,
+    /// Mempool indexes of Transactions.
+    Index,
+    /// Inner nodes of Merkle trees.
+    Merkle,
+    /// Fingerprints of [Chains](crate::chain::Chain).
+    Fingerprint,
+    /// Seeds used to select the leader of a slot.
+    Leader,
+    /// Checksums of encoded addresses.
+    Address,
+}
+
+impl Domain {
+    /// Returns the tag which prefixes the data hashed in the domain.
+    fn tag(self) -> &'static [u8] {
+        match self {
+            Domain::Transaction => b"anova/transaction",
+            Domain::Block => b"anova/block",
+            Domain::Sender => b"anova/sender",
+            Domain::Index => b"anova/index",
+            Domain::Merkle => b"anova/merkle",
+            Domain::Fingerprint => b"anova/fingerprint",
+            Domain::Leader => b"anova/leader",
+            Domain::Address => b"anova/address",
+        }
+    }
+}
+
+/// Creates a Keccak256 hash of the given data in the given domain.
+pub(crate) fn hash<T: AsRef<[u8]>>(domain: Domain, data: T) -> Keccak256 {
     let mut hasher = sha3::Keccak256::new();
+    hasher.update(domain.tag());
     hasher.update(data);
-    hasher.finalize().as_slice().to_vec()
+    let mut bytes = [0; 32];
+    bytes.copy_from_slice(hasher.finalize().as_slice());
+    Hash256(bytes)
+}
+
+/// Creates a Keccak256 hash of the given data in the Transaction domain.
+pub(crate) fn hash_tx<T: AsRef<[u8]>>(data: T) -> Keccak256 {
+    hash(Domain::Transaction, data)
+}
+
+/// Creates a Keccak256 hash of the given data in the Block domain.
+pub(crate) fn hash_block<T: AsRef<[u8]>>(data: T) -> Keccak256 {
+    hash(Domain::Block, data)
+}
+
+/// Encodes the given bytes as a lowercase hex string.
+pub(crate) fn to_hex<T: AsRef<[u8]>>(data: T) -> String {
+    data.as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Number of checksum bytes appended to an encoded address.
+const ADDRESS_CHECKSUM_BYTES: usize = 4;
+
+/// Errors which can occur when decoding an address.
+#[derive(Debug, PartialEq)]
+pub enum AddressError {
+    /// The address isn't a valid hex string.
+    InvalidEncoding,
+    /// The address is too short to contain a checksum.
+    TooShort,
+    /// The checksum doesn't match the encoded data.
+    InvalidChecksum,
+}
+
+/// Encodes the given public key hash as a hex string with the first 4 bytes of
+/// its Keccak256 hash appended as a checksum.
+pub fn encode_address(pubkey_hash: &[u8]) -> String {
+    let checksum = hash(Domain::Address, pubkey_hash);
+    let mut bytes = pubkey_hash.to_vec();
+    bytes.extend_from_slice(&checksum.as_bytes()[..ADDRESS_CHECKSUM_BYTES]);
+    to_hex(bytes)
+}
+
+/// Decodes an address created via [encode_address] and verifies its checksum.
+pub fn decode_address(address: &str) -> Result<Vec<u8>, AddressError> {
+    let digits = address
+        .chars()
+        .map(|c| c.to_digit(16).map(|digit| digit as u8))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or(AddressError::InvalidEncoding)?;
+    if digits.len() % 2 != 0 {
+        return Err(AddressError::InvalidEncoding);
+    }
+    let mut bytes: Vec<u8> = digits
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair[1])
+        .collect();
+    if bytes.len() < ADDRESS_CHECKSUM_BYTES {
+        return Err(AddressError::TooShort);
+    }
+
+    let checksum = bytes.split_off(bytes.len() - ADDRESS_CHECKSUM_BYTES);
+    if checksum[..] != hash(Domain::Address, &bytes).as_bytes()[..ADDRESS_CHECKSUM_BYTES] {
+        return Err(AddressError::InvalidChecksum);
+    }
+    Ok(bytes)
+}
+
+/// Compares the given bytes in constant time, i.e. without returning early at
+/// the first differing byte. Only the lengths are compared in variable time.
+pub(crate) fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash256() {
+        let digest = hash(Domain::Block, b"");
+        assert_eq!(digest.as_bytes().len(), 32);
+        assert_eq!(std::mem::size_of::<Hash256>(), 32);
+
+        // A Hash256 is encoded just like the underlying bytes.
+        let bytes: Vec<u8> = digest.clone().into();
+        assert_eq!(
+            bincode::serialize(&digest).unwrap(),
+            bincode::serialize(&bytes).unwrap()
+        );
+
+        let deserialized: Hash256 =
+            bincode::deserialize(&bincode::serialize(&digest).unwrap()).unwrap();
+        assert_eq!(deserialized, digest);
+
+        // A FixedHash omits the length prefix.
+        let serialized = bincode::serialize(&FixedHash(&digest)).unwrap();
+        assert_eq!(serialized, digest.as_bytes());
+
+        // Only 32 bytes can be decoded into a Hash256.
+        let serialized = bincode::serialize(&vec![0u8; 5]).unwrap();
+        assert!(bincode::deserialize::<Hash256>(&serialized).is_err());
+    }
+
+    #[test]
+    fn domain_separation() {
+        let domains = [
+            Domain::Transaction,
+            Domain::Block,
+            Domain::Sender,
+            Domain::Index,
+            Domain::Merkle,
+            Domain::Fingerprint,
+            Domain::Leader,
+            Domain::Address,
+        ];
+        let data = [1u8, 2, 3];
+        for (i, a) in domains.iter().enumerate() {
+            for b in domains[i + 1..].iter() {
+                assert_ne!(hash(*a, data), hash(*b, data));
+                // Tags which prefix each other would let data move between domains.
+                assert!(!a.tag().starts_with(b.tag()));
+                assert!(!b.tag().starts_with(a.tag()));
+            }
+        }
+
+        // The Transaction and Block helpers hash in their domains.
+        assert_eq!(hash_tx(data), hash(Domain::Transaction, data));
+        assert_eq!(hash_block(data), hash(Domain::Block, data));
+        assert_ne!(hash_tx(data), hash_block(data));
+    }
+
+    #[test]
+    fn address() {
+        let sender = hash(Domain::Sender, b"public key").as_bytes().to_vec();
+        let address = encode_address(&sender);
+        assert_eq!(address.len(), (32 + ADDRESS_CHECKSUM_BYTES) * 2);
+        assert!(address.starts_with(&to_hex(&sender)));
+        assert_eq!(decode_address(&address), Ok(sender));
+
+        // Changing any single character is detected.
+        for position in 0..address.len() {
+            let mut corrupted: Vec<char> = address.chars().collect();
+            corrupted[position] = if corrupted[position] == '0' { '1' } else { '0' };
+            let corrupted: String = corrupted.into_iter().collect();
+            assert_eq!(
+                decode_address(&corrupted),
+                Err(AddressError::InvalidChecksum)
+            );
+        }
+
+        assert_eq!(
+            decode_address(&address[1..]),
+            Err(AddressError::InvalidEncoding)
+        );
+        assert_eq!(decode_address("zz"), Err(AddressError::InvalidEncoding));
+        assert_eq!(decode_address("00ff"), Err(AddressError::TooShort));
+        assert_eq!(decode_address(&encode_address(&[])), Ok(vec![]));
+    }
+
+    #[test]
+    fn ct_eq() {
+        let inputs: Vec<Vec<u8>> = vec![
+            vec![],
+            vec![1],
+            vec![1, 2],
+            vec![1, 3],
+            vec![1, 2, 3],
+            vec![0; 32],
+            vec![1; 32],
+        ];
+        for a in inputs.iter() {
+            for b in inputs.iter() {
+                assert_eq!(super::ct_eq(a, b), a == b);
+            }
+        }
+    }
 }
@@ -0,0 +1,105 @@
+//! Golden vectors which pin the ids of Transactions and Blocks. Ids are derived
+//! from the binary representations, so any change to an encoding shows up here
+//! first.
+
+use anova::block::{Block, BlockLimits};
+use anova::transaction::Transaction;
+use anova::Hash256;
+
+/// Encodes the given bytes as a lowercase hex string.
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Asserts that the given id matches the expected hex string. Fails with both
+/// ids and a marker below every differing character otherwise.
+fn assert_id(name: &str, actual: &Hash256, expected: &str) {
+    let actual = to_hex(actual.as_bytes());
+    if actual == expected {
+        return;
+    }
+
+    let diff: String = actual
+        .chars()
+        .zip(expected.chars())
+        .map(|(a, e)| if a == e { ' ' } else { '^' })
+        .collect();
+    panic!(
+        "id of {} changed\nexpected: {}\n  actual: {}\n          {}",
+        name, expected, actual, diff
+    );
+}
+
+fn tx_1() -> Transaction {
+    Transaction::new(vec![0, 1, 2, 3, 4], 1)
+}
+
+fn tx_2() -> Transaction {
+    Transaction::new(vec![5, 6, 7, 8, 9], 2)
+}
+
+#[test]
+fn transaction_ids() {
+    assert_id(
+        "transaction with nonce 1",
+        &tx_1().id,
+        "b48752612e4ce2a3e26e0a280eaf60b0d855c728097e97510ea4966d683e3680",
+    );
+    assert_id(
+        "transaction with nonce 2",
+        &tx_2().id,
+        "eab9fb0e795a38176dd67d47520a9ff6f0011f0435896d37677a7947452f8750",
+    );
+
+    let sender = Transaction::derive_sender(&[1, 2, 3, 4, 5]);
+    assert_id(
+        "transaction with a derived sender",
+        &Transaction::new(sender, 0).id,
+        "6466760c7a5d0f42b41484bffd3bc3eb205a2a5baa4c7320f9dbdfcedf1e2688",
+    );
+}
+
+#[test]
+fn genesis_block_id() {
+    assert_id(
+        "genesis block",
+        &Block::genesis().id,
+        "c914fcfa9383675542316b545de1d498dc8907de89126dce0737a5b94114f282",
+    );
+}
+
+#[test]
+fn block_ids() {
+    let genesis = Block::genesis();
+    assert_id(
+        "block linked to the genesis block",
+        &Block::new(vec![tx_1()], Some(genesis.id)).id,
+        "98ef8feca1f15232d792fed37785524e6f47977ab1aa4f899a8d6fd94298f797",
+    );
+    assert_id(
+        "block with multiple transactions",
+        &Block::new(vec![tx_1(), tx_2()], None).id,
+        "da5c933ca6b9835567288c83352cc1d98f2317dc01eb76fa28a73ca85991e8ec",
+    );
+
+    let limits = BlockLimits {
+        max_txs: 10,
+        max_bytes: 1024,
+    };
+    let block = Block::try_new(vec![tx_1()], None, b"anova".to_vec(), &limits).unwrap();
+    assert_id(
+        "block with extra data",
+        &block.id,
+        "089c5f88ea81ca0212ddb2f3d1e9ba2fd28a9f20aa025ef39e6fbc1df1eae6e5",
+    );
+}
+
+#[test]
+#[should_panic(expected = "id of genesis block changed")]
+fn assert_id_reports_changes() {
+    assert_id(
+        "genesis block",
+        &Block::genesis().id,
+        "0000000000000000000000000000000000000000000000000000000000000000",
+    );
+}